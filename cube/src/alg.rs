@@ -0,0 +1,278 @@
+use crate::{parse_moves, Face, Move, ParseError, Rotation, Slice};
+
+/// A sequence of moves, with operations to transform it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Alg(pub Vec<Move>);
+
+/// Get the axis, represented by its `Slice`, that `m` turns around.
+fn axis(m: &Move) -> Slice {
+  match m {
+    Move::Face(f, _) | Move::Wide(f, _) => f.slice(),
+    Move::Slice(s, _) => *s,
+    Move::Rotation(Rotation::X, _) => Slice::M,
+    Move::Rotation(Rotation::Y, _) => Slice::E,
+    Move::Rotation(Rotation::Z, _) => Slice::S,
+  }
+}
+
+/// The `Face` that a `Slice` turns in the same direction as.
+fn slice_face(s: Slice) -> Face {
+  match s {
+    Slice::M => Face::L,
+    Slice::E => Face::D,
+    Slice::S => Face::F,
+  }
+}
+
+impl Alg {
+  /// Get the sequence that undoes `self`.
+  pub fn inverse(&self) -> Alg {
+    Alg(self.0.iter().rev().map(Move::inverse).collect())
+  }
+
+  /// Mirror the sequence through the plane of the slice `s`. For example,
+  /// mirroring through `M` swaps `R` and `L`, so `R U R'` becomes `L' U' L`.
+  pub fn mirror(&self, s: Slice) -> Alg {
+    Alg(
+      self
+        .0
+        .iter()
+        .map(|&m| {
+          // Every move changes direction, except for slices and rotations
+          // that are parallel to the mirror.
+          let m = match m {
+            Move::Face(f, a) if f.slice() == s => Move::Face(f.opposite(), a),
+            Move::Wide(f, a) if f.slice() == s => Move::Wide(f.opposite(), a),
+            _ if axis(&m) == s => return m,
+            _ => m,
+          };
+          m.inverse()
+        })
+        .collect(),
+    )
+  }
+
+  /// Merge moves that turn the same layers, e.g. `R R2` becomes `R'` and
+  /// `R L R'` becomes `L`. Moves are only merged across other moves on the
+  /// same axis, since those commute.
+  pub fn cancel(&self) -> Alg {
+    let mut moves: Vec<Move> = Vec::with_capacity(self.0.len());
+    'next: for &m in &self.0 {
+      for i in (0..moves.len()).rev() {
+        let prev = moves[i];
+        if axis(&prev) != axis(&m) {
+          break;
+        }
+        if prev.is_same_movement(&m) {
+          let amount = (prev.amount() + m.amount()) % 4;
+          if amount == 0 {
+            moves.remove(i);
+          } else {
+            moves[i] = m.with_amount(amount);
+          }
+          continue 'next;
+        }
+      }
+      moves.push(m);
+    }
+    Alg(moves)
+  }
+
+  /// Remove the rotations, relabelling the faces of the moves that follow
+  /// them. For example, `x U` becomes `F`. The result has the same effect on
+  /// the pieces, but leaves the cube in its starting orientation.
+  pub fn without_rotations(&self) -> Alg {
+    use crate::Face::*;
+
+    // Map from the current name of a face to its original name, indexed by
+    // `CentrePos`.
+    let idx = |f: Face| match f {
+      U => 0,
+      R => 1,
+      F => 2,
+      D => 3,
+      B => 4,
+      L => 5,
+    };
+    let mut map = [U, R, F, D, B, L];
+
+    let mut moves = Vec::with_capacity(self.0.len());
+    for &m in &self.0 {
+      match m {
+        Move::Face(f, a) => moves.push(Move::Face(map[idx(f)], a)),
+        Move::Wide(f, a) => moves.push(Move::Wide(map[idx(f)], a)),
+        Move::Slice(s, a) => {
+          let f = map[idx(slice_face(s))];
+          let s = f.slice();
+          let a = if f == slice_face(s) { a } else { 4 - a };
+          moves.push(Move::Slice(s, a));
+        }
+        Move::Rotation(r, a) => {
+          // The faces that move into each other, e.g. x moves F to U.
+          let cycle = match r {
+            Rotation::X => [U, F, D, B],
+            Rotation::Y => [F, R, B, L],
+            Rotation::Z => [R, U, L, D],
+          };
+          for _ in 0..a {
+            let old = map;
+            for i in 0..4 {
+              map[idx(cycle[i])] = old[idx(cycle[(i + 1) % 4])];
+            }
+          }
+        }
+      }
+    }
+    Alg(moves)
+  }
+}
+
+impl std::ops::Deref for Alg {
+  type Target = [Move];
+
+  fn deref(&self) -> &[Move] {
+    &self.0
+  }
+}
+
+impl From<Vec<Move>> for Alg {
+  fn from(moves: Vec<Move>) -> Alg {
+    Alg(moves)
+  }
+}
+
+impl std::str::FromStr for Alg {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Alg, ParseError> {
+    parse_moves(s).map(Alg)
+  }
+}
+
+impl std::fmt::Display for Alg {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    for (i, m) in self.0.iter().enumerate() {
+      if i > 0 {
+        write!(f, " ")?;
+      }
+      write!(f, "{}", m)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Cube;
+  use proptest::prelude::*;
+
+  fn alg(s: &str) -> Alg {
+    s.parse().unwrap()
+  }
+
+  fn any_move() -> impl Strategy<Value = Move> {
+    use crate::Face::*;
+    let faces =
+      prop_oneof![Just(U), Just(D), Just(F), Just(B), Just(R), Just(L)];
+    let slices = prop_oneof![Just(Slice::M), Just(Slice::E), Just(Slice::S)];
+    let rotations =
+      prop_oneof![Just(Rotation::X), Just(Rotation::Y), Just(Rotation::Z)];
+    (
+      prop_oneof![
+        faces.clone().prop_map(|f| Move::Face(f, 1)),
+        faces.prop_map(|f| Move::Wide(f, 1)),
+        slices.prop_map(|s| Move::Slice(s, 1)),
+        rotations.prop_map(|r| Move::Rotation(r, 1)),
+      ],
+      1..4u8,
+    )
+      .prop_map(|(m, a)| m.with_amount(a))
+  }
+
+  fn any_alg() -> impl Strategy<Value = Alg> {
+    prop::collection::vec(any_move(), 0..20).prop_map(Alg)
+  }
+
+  fn apply(alg: &Alg) -> Cube {
+    let mut c = Cube::solved();
+    c.do_moves(alg);
+    c
+  }
+
+  /// The number of times `alg` has to be repeated to get back to solved.
+  fn order(alg: &Alg) -> usize {
+    let mut c = Cube::solved();
+    for n in 1.. {
+      c.do_moves(alg);
+      if c == Cube::solved() {
+        return n;
+      }
+    }
+    unreachable!()
+  }
+
+  #[test]
+  fn examples() {
+    assert_eq!(alg("R U R' U'"), alg("U R U' R'").inverse());
+    assert_eq!(alg("L' U' L U"), alg("R U R' U'").mirror(Slice::M));
+    assert_eq!(alg("M x r l'"), alg("M x l' r").mirror(Slice::M));
+    assert_eq!(alg("B' U' B S z"), alg("F U F' S z").mirror(Slice::S));
+    assert_eq!(alg("R'"), alg("R R2").cancel());
+    assert_eq!(alg("L"), alg("R L R'").cancel());
+    assert_eq!(alg("R2 U R"), alg("R U U' R U R M M'").cancel());
+    assert_eq!(alg(""), alg("R U U' R'").cancel());
+    assert_eq!(alg("F"), alg("x U").without_rotations());
+    assert_eq!(alg("R S' R"), alg("R y M' y' z' U").without_rotations());
+    assert_eq!("R U2 r'", alg("R U2 r'").to_string());
+  }
+
+  proptest! {
+    #[test]
+    fn inverse(a in any_alg()) {
+      let mut c = apply(&a);
+      c.do_moves(&a.inverse());
+      prop_assert_eq!(Cube::solved(), c);
+      prop_assert_eq!(&a, &a.inverse().inverse());
+    }
+
+    #[test]
+    fn mirror(a in prop::collection::vec(any_move(), 0..6).prop_map(Alg)) {
+      for &s in &[Slice::M, Slice::E, Slice::S] {
+        let mirrored = a.mirror(s);
+        prop_assert_eq!(&a, &mirrored.mirror(s));
+        prop_assert_eq!(mirrored.inverse(), a.inverse().mirror(s));
+        prop_assert_eq!(order(&a), order(&mirrored));
+      }
+    }
+
+    #[test]
+    fn cancel(a in any_alg()) {
+      let cancelled = a.cancel();
+      prop_assert_eq!(apply(&a), apply(&cancelled));
+      prop_assert!(cancelled.len() <= a.len());
+      prop_assert!(cancelled.windows(2).all(|w| !w[0].is_same_movement(&w[1])));
+      prop_assert_eq!(&cancelled, &cancelled.cancel());
+    }
+
+    #[test]
+    fn without_rotations(a in any_alg()) {
+      let rewritten = a.without_rotations();
+      prop_assert!(!rewritten.iter().any(|m| matches!(m, Move::Rotation(..))));
+
+      // The cubes should only differ by a rotation.
+      let c = apply(&a);
+      let c2 = apply(&rewritten);
+      let ups = ["", "x", "x2", "x'", "z", "z'"];
+      let rotated = ups.iter().any(|up| (0..4).any(|n| {
+        let mut c2 = c2;
+        c2.do_moves(&alg(up));
+        for _ in 0..n {
+          c2.do_move(Move::Rotation(Rotation::Y, 1));
+        }
+        c2 == c
+      }));
+      prop_assert!(rotated);
+    }
+  }
+}
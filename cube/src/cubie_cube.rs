@@ -0,0 +1,430 @@
+use crate::sticker_cube::{CentrePos, Cube};
+use crate::{Face, Move, Rotation, Slice};
+
+/// Represents a 3x3x3 cube by the permutation and orientation of its pieces.
+///
+/// Corners are numbered in the order URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB
+/// and edges in the order UF, UL, UB, UR, DF, DL, DB, DR, FR, FL, BL, BR,
+/// which is the same order as `CornerPos` and `EdgePos`. `cp[i]` is the piece
+/// at position `i` and `co[i]` is its orientation. A corner's orientation is
+/// which of its facelets, in `CornerPos` order, holds its U or D sticker. An
+/// edge's orientation is 0 if its U or D sticker (or F or B sticker, for E
+/// slice edges) is on the first facelet in `EdgePos` order, and 1 otherwise.
+///
+/// There are no centres, so the orientation of the whole cube is not
+/// represented.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubieCube {
+  pub cp: [u8; 8],
+  pub co: [u8; 8],
+  pub ep: [u8; 12],
+  pub eo: [u8; 12],
+}
+
+impl CubieCube {
+  /// Creates a solved cube.
+  pub fn solved() -> CubieCube {
+    CubieCube {
+      cp: [0, 1, 2, 3, 4, 5, 6, 7],
+      co: [0; 8],
+      ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+      eo: [0; 12],
+    }
+  }
+
+  /// Convert a sticker `Cube` into a `CubieCube`. The stickers are read
+  /// relative to the centres, so a cube that has only been rotated is solved.
+  /// Returns `None` if the stickers do not make up each piece exactly once.
+  pub fn from_cube(c: &Cube) -> Option<CubieCube> {
+    // Map each sticker to the name of the position whose centre matches it.
+    let mut relabel = [Face::U; 6];
+    for (pos, &centre) in c.centres.iter().enumerate() {
+      relabel[centre_pos(centre)] = CENTRE_FACES[pos];
+    }
+    let sticker = |f: Face| relabel[centre_pos(f)];
+
+    let solved = Cube::solved();
+    let mut cc = CubieCube::solved();
+
+    for i in 0..8 {
+      let f = [
+        sticker(c.corners[3 * i]),
+        sticker(c.corners[3 * i + 1]),
+        sticker(c.corners[3 * i + 2]),
+      ];
+      let o = f.iter().position(|&f| f == Face::U || f == Face::D)?;
+      let piece = (f[o], f[(o + 1) % 3], f[(o + 2) % 3]);
+      cc.cp[i] = (0..8).find(|&j| {
+        let s = &solved.corners[3 * j..3 * j + 3];
+        piece == (s[0], s[1], s[2])
+      })? as u8;
+      cc.co[i] = o as u8;
+    }
+
+    for i in 0..12 {
+      let f = (sticker(c.edges[2 * i]), sticker(c.edges[2 * i + 1]));
+      let (j, o) = (0..12).find_map(|j| {
+        let s = (solved.edges[2 * j], solved.edges[2 * j + 1]);
+        if f == s {
+          Some((j, 0))
+        } else if (f.1, f.0) == s {
+          Some((j, 1))
+        } else {
+          None
+        }
+      })?;
+      cc.ep[i] = j as u8;
+      cc.eo[i] = o;
+    }
+
+    let mut corners = cc.cp;
+    corners.sort_unstable();
+    let mut edges = cc.ep;
+    edges.sort_unstable();
+    if corners != CubieCube::solved().cp || edges != CubieCube::solved().ep {
+      return None;
+    }
+
+    Some(cc)
+  }
+
+  /// Convert to a sticker `Cube`, with the centres in their default state.
+  /// The orientation of the whole cube is not stored, so a cube that was
+  /// rotated comes back held the default way, and differs from the `Cube`
+  /// it was made from.
+  pub fn to_cube(&self) -> Cube {
+    let solved = Cube::solved();
+    let mut c = solved;
+
+    for i in 0..8 {
+      let j = self.cp[i] as usize;
+      let o = self.co[i] as usize;
+      for k in 0..3 {
+        c.corners[3 * i + (k + o) % 3] = solved.corners[3 * j + k];
+      }
+    }
+
+    for i in 0..12 {
+      let j = self.ep[i] as usize;
+      let o = self.eo[i] as usize;
+      for k in 0..2 {
+        c.edges[2 * i + (k + o) % 2] = solved.edges[2 * j + k];
+      }
+    }
+
+    c
+  }
+
+  /// Get the state of doing `self` followed by `other`.
+  pub fn multiply(&self, other: &CubieCube) -> CubieCube {
+    let mut res = CubieCube::solved();
+    for i in 0..8 {
+      let j = other.cp[i] as usize;
+      res.cp[i] = self.cp[j];
+      res.co[i] = (self.co[j] + other.co[i]) % 3;
+    }
+    for i in 0..12 {
+      let j = other.ep[i] as usize;
+      res.ep[i] = self.ep[j];
+      res.eo[i] = (self.eo[j] + other.eo[i]) % 2;
+    }
+    res
+  }
+
+  /// Get the state that undoes `self`, so that `self.multiply(&inverse)` is
+  /// solved.
+  pub fn inverse(&self) -> CubieCube {
+    let mut res = CubieCube::solved();
+    for i in 0..8 {
+      let j = self.cp[i] as usize;
+      res.cp[j] = i as u8;
+      res.co[j] = (3 - self.co[i]) % 3;
+    }
+    for i in 0..12 {
+      let j = self.ep[i] as usize;
+      res.ep[j] = i as u8;
+      res.eo[j] = self.eo[i];
+    }
+    res
+  }
+
+  /// Do the move `m` on the cube. Moves that turn the centres are seen from
+  /// the cube's new orientation, as with `CubieCube::from_cube`. A rotation
+  /// relabels the pieces, and slice and wide moves are face turns followed
+  /// by a rotation.
+  pub fn do_move(&mut self, m: Move) {
+    use Face::*;
+    match m {
+      Move::Face(f, amt) => self.turn(f, amt),
+      Move::Slice(Slice::M, amt) => {
+        self.turn(R, amt);
+        self.turn(L, 4 - amt);
+        self.rotate(Rotation::X, 4 - amt);
+      }
+      Move::Slice(Slice::E, amt) => {
+        self.turn(U, amt);
+        self.turn(D, 4 - amt);
+        self.rotate(Rotation::Y, 4 - amt);
+      }
+      Move::Slice(Slice::S, amt) => {
+        self.turn(F, 4 - amt);
+        self.turn(B, amt);
+        self.rotate(Rotation::Z, amt);
+      }
+      Move::Rotation(r, amt) => self.rotate(r, amt),
+      Move::Wide(f, amt) => {
+        self.turn(f.opposite(), amt);
+        let (r, amt) = match f {
+          R => (Rotation::X, amt),
+          L => (Rotation::X, 4 - amt),
+          U => (Rotation::Y, amt),
+          D => (Rotation::Y, 4 - amt),
+          F => (Rotation::Z, amt),
+          B => (Rotation::Z, 4 - amt),
+        };
+        self.rotate(r, amt);
+      }
+    }
+  }
+
+  /// Turn face `f` clockwise `amt` times.
+  fn turn(&mut self, f: Face, amt: u8) {
+    for _ in 0..amt % 4 {
+      *self = self.multiply(&FACE_MOVES[centre_pos(f)]);
+    }
+  }
+
+  /// Rotate the whole cube by `r` `amt` times. The pieces are read relative
+  /// to the centres, so this conjugates the state by the rotation.
+  fn rotate(&mut self, r: Rotation, amt: u8) {
+    let rotation = &ROTATIONS[r as usize];
+    for _ in 0..amt % 4 {
+      *self = rotation.inverse().multiply(self).multiply(rotation);
+    }
+  }
+
+  /// Do all the moves in the slice `moves` on the cube.
+  pub fn do_moves(&mut self, moves: &[Move]) {
+    for &m in moves {
+      self.do_move(m);
+    }
+  }
+}
+
+impl Default for CubieCube {
+  fn default() -> Self {
+    CubieCube::solved()
+  }
+}
+
+/// The result of each face turn, in `CentrePos` order.
+static FACE_MOVES: [CubieCube; 6] = [
+  // U
+  CubieCube {
+    cp: [3, 0, 1, 2, 4, 5, 6, 7],
+    co: [0, 0, 0, 0, 0, 0, 0, 0],
+    ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+    eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+  },
+  // R
+  CubieCube {
+    cp: [4, 1, 2, 0, 7, 5, 6, 3],
+    co: [2, 0, 0, 1, 1, 0, 0, 2],
+    ep: [0, 1, 2, 8, 4, 5, 6, 11, 7, 9, 10, 3],
+    eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+  },
+  // F
+  CubieCube {
+    cp: [1, 5, 2, 3, 0, 4, 6, 7],
+    co: [1, 2, 0, 0, 2, 1, 0, 0],
+    ep: [9, 1, 2, 3, 8, 5, 6, 7, 0, 4, 10, 11],
+    eo: [1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0],
+  },
+  // D
+  CubieCube {
+    cp: [0, 1, 2, 3, 5, 6, 7, 4],
+    co: [0, 0, 0, 0, 0, 0, 0, 0],
+    ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+    eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+  },
+  // B
+  CubieCube {
+    cp: [0, 1, 3, 7, 4, 5, 2, 6],
+    co: [0, 0, 1, 2, 0, 0, 2, 1],
+    ep: [0, 1, 11, 3, 4, 5, 10, 7, 8, 9, 2, 6],
+    eo: [0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 1],
+  },
+  // L
+  CubieCube {
+    cp: [0, 2, 6, 3, 4, 1, 5, 7],
+    co: [0, 1, 2, 0, 0, 2, 1, 0],
+    ep: [0, 10, 2, 3, 4, 9, 6, 7, 8, 1, 5, 11],
+    eo: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+  },
+];
+
+/// How each rotation moves the pieces, ignoring the centres, in `Rotation`
+/// order.
+static ROTATIONS: [CubieCube; 3] = [
+  // x
+  CubieCube {
+    cp: [4, 5, 1, 0, 7, 6, 2, 3],
+    co: [2, 1, 2, 1, 1, 2, 1, 2],
+    ep: [4, 9, 0, 8, 6, 10, 2, 11, 7, 5, 1, 3],
+    eo: [1, 0, 1, 0, 1, 0, 1, 0, 0, 0, 0, 0],
+  },
+  // y
+  CubieCube {
+    cp: [3, 0, 1, 2, 7, 4, 5, 6],
+    co: [0, 0, 0, 0, 0, 0, 0, 0],
+    ep: [3, 0, 1, 2, 7, 4, 5, 6, 11, 8, 9, 10],
+    eo: [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+  },
+  // z
+  CubieCube {
+    cp: [1, 5, 6, 2, 0, 4, 7, 3],
+    co: [1, 2, 1, 2, 2, 1, 2, 1],
+    ep: [9, 5, 10, 1, 8, 7, 11, 3, 0, 4, 6, 2],
+    eo: [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+  },
+];
+
+/// The faces in `CentrePos` order.
+const CENTRE_FACES: [Face; 6] =
+  [Face::U, Face::R, Face::F, Face::D, Face::B, Face::L];
+
+/// Get the `CentrePos` index of a face.
+fn centre_pos(f: Face) -> usize {
+  (match f {
+    Face::U => CentrePos::U,
+    Face::R => CentrePos::R,
+    Face::F => CentrePos::F,
+    Face::D => CentrePos::D,
+    Face::B => CentrePos::B,
+    Face::L => CentrePos::L,
+  }) as usize
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse_moves;
+
+  fn cubie(moves: &str) -> CubieCube {
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves(moves).unwrap());
+    CubieCube::from_cube(&c).unwrap()
+  }
+
+  #[test]
+  fn face_moves() {
+    for (i, &f) in CENTRE_FACES.iter().enumerate() {
+      let mut c = Cube::solved();
+      c.do_move(Move::Face(f, 1));
+      assert_eq!(FACE_MOVES[i], CubieCube::from_cube(&c).unwrap());
+    }
+  }
+
+  #[test]
+  fn rotations() {
+    for (i, &r) in [Rotation::X, Rotation::Y, Rotation::Z].iter().enumerate() {
+      // Read the pieces without relabelling them by the moved centres.
+      let mut c = Cube::solved();
+      c.do_move(Move::Rotation(r, 1));
+      c.centres = Cube::solved().centres;
+      assert_eq!(ROTATIONS[i], CubieCube::from_cube(&c).unwrap());
+    }
+  }
+
+  #[test]
+  fn round_trip() {
+    assert_eq!(CubieCube::solved(), cubie(""));
+    assert_eq!(Cube::solved(), CubieCube::solved().to_cube());
+
+    let scramble = "D2 F2 U' B2 R2 U F2 L2 U' R' B' L F' D L' U' F U2 R B";
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves(scramble).unwrap());
+    assert_eq!(c, CubieCube::from_cube(&c).unwrap().to_cube());
+
+    // Rotations and slice moves are read relative to the centres.
+    assert_eq!(CubieCube::solved(), cubie("x y2 z'"));
+    assert_eq!(cubie("R L'"), cubie("M x"));
+    assert_eq!(cubie("F"), cubie("x U x'"));
+
+    // The orientation of the whole cube is lost, so this is R from the
+    // default orientation.
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("x y R").unwrap());
+    let round_trip = CubieCube::from_cube(&c).unwrap().to_cube();
+    assert_ne!(c, round_trip);
+    let mut expected = Cube::solved();
+    expected.do_moves(&parse_moves("R").unwrap());
+    assert_eq!(expected, round_trip);
+  }
+
+  #[test]
+  fn every_move() {
+    let start = cubie("R U2 F' L D B2 R' U");
+    let faces = [Face::U, Face::D, Face::F, Face::B, Face::R, Face::L];
+    let moves = faces
+      .iter()
+      .flat_map(|&f| vec![Move::Face(f, 1), Move::Wide(f, 1)])
+      .chain(
+        [Slice::M, Slice::E, Slice::S]
+          .iter()
+          .map(|&s| Move::Slice(s, 1)),
+      )
+      .chain(
+        [Rotation::X, Rotation::Y, Rotation::Z]
+          .iter()
+          .map(|&r| Move::Rotation(r, 1)),
+      );
+    for m in moves {
+      for amt in 1..=3 {
+        let m = m.with_amount(amt);
+        let mut c = start.to_cube();
+        c.do_move(m);
+        let mut cc = start;
+        cc.do_move(m);
+        assert_eq!(CubieCube::from_cube(&c).unwrap(), cc, "{}", m);
+      }
+    }
+  }
+
+  #[test]
+  fn invalid() {
+    assert_eq!(None, CubieCube::from_cube(&Cube::invalid()));
+
+    // Two UF edges.
+    let mut c = Cube::solved();
+    c.edges[2] = Face::U;
+    c.edges[3] = Face::F;
+    assert_eq!(None, CubieCube::from_cube(&c));
+
+    // A corner with two U stickers.
+    let mut c = Cube::solved();
+    c.corners[1] = Face::U;
+    assert_eq!(None, CubieCube::from_cube(&c));
+  }
+
+  #[test]
+  fn multiply() {
+    let a = "R U R' U' F2 D L'";
+    let b = "B' L2 D' R F U2";
+    assert_eq!(cubie(&format!("{} {}", a, b)), cubie(a).multiply(&cubie(b)));
+
+    let mut c = CubieCube::solved();
+    c.do_moves(&parse_moves(a).unwrap());
+    assert_eq!(cubie(a), c);
+    c.do_moves(&parse_moves("M' E2 S r").unwrap());
+    assert_eq!(cubie(&format!("{} M' E2 S r", a)), c);
+  }
+
+  #[test]
+  fn inverse() {
+    let c = cubie("R U2 F' L D B2 R' U");
+    assert_eq!(CubieCube::solved(), c.multiply(&c.inverse()));
+    assert_eq!(CubieCube::solved(), c.inverse().multiply(&c));
+    assert_eq!(cubie("U' R B2 D' L' F U2 R'"), c.inverse());
+  }
+}
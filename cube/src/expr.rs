@@ -0,0 +1,193 @@
+use crate::Move;
+
+/// An algorithm written in compact notation, e.g. `[R U R', D]`,
+/// `[F: R U R' U']` or `(R U R' U')3`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlgExpr {
+  /// A single move.
+  Move(Move),
+  /// A sequence of expressions, done one after another.
+  Seq(Vec<AlgExpr>),
+  /// An expression in parentheses, `(A)`.
+  Paren(Box<AlgExpr>),
+  /// An expression in brackets, `[A]`.
+  Bracket(Box<AlgExpr>),
+  /// A commutator, `[A, B]`, which expands to `A B A' B'`.
+  Commutator(Box<AlgExpr>, Box<AlgExpr>),
+  /// A conjugate, `[A: B]`, which expands to `A B A'`.
+  Conjugate(Box<AlgExpr>, Box<AlgExpr>),
+  /// An expression repeated a number of times, e.g. `(A)3`. A negative count
+  /// repeats the inverse, e.g. `(A)'` or `(A)2'`.
+  Repeat(Box<AlgExpr>, i32),
+}
+
+impl AlgExpr {
+  /// Expand the expression into a flat list of moves.
+  pub fn expand(&self) -> Vec<Move> {
+    let mut moves = Vec::new();
+    self.expand_into(&mut moves);
+    moves
+  }
+
+  /// Get the number of moves `expand` gives, without expanding. This
+  /// saturates rather than overflowing.
+  pub fn expanded_len(&self) -> usize {
+    match self {
+      AlgExpr::Move(_) => 1,
+      AlgExpr::Seq(exprs) => exprs
+        .iter()
+        .fold(0, |len, e| len.saturating_add(e.expanded_len())),
+      AlgExpr::Paren(e) | AlgExpr::Bracket(e) => e.expanded_len(),
+      AlgExpr::Commutator(a, b) => a
+        .expanded_len()
+        .saturating_add(b.expanded_len())
+        .saturating_mul(2),
+      AlgExpr::Conjugate(a, b) => a
+        .expanded_len()
+        .saturating_mul(2)
+        .saturating_add(b.expanded_len()),
+      AlgExpr::Repeat(e, n) => {
+        e.expanded_len().saturating_mul(n.unsigned_abs() as usize)
+      }
+    }
+  }
+
+  fn expand_into(&self, moves: &mut Vec<Move>) {
+    match self {
+      AlgExpr::Move(m) => moves.push(*m),
+      AlgExpr::Seq(exprs) => {
+        for e in exprs {
+          e.expand_into(moves);
+        }
+      }
+      AlgExpr::Paren(e) | AlgExpr::Bracket(e) => e.expand_into(moves),
+      AlgExpr::Commutator(a, b) => {
+        a.expand_into(moves);
+        b.expand_into(moves);
+        a.expand_inverse_into(moves);
+        b.expand_inverse_into(moves);
+      }
+      AlgExpr::Conjugate(a, b) => {
+        a.expand_into(moves);
+        b.expand_into(moves);
+        a.expand_inverse_into(moves);
+      }
+      AlgExpr::Repeat(e, n) => {
+        for _ in 0..n.abs() {
+          if *n > 0 {
+            e.expand_into(moves);
+          } else {
+            e.expand_inverse_into(moves);
+          }
+        }
+      }
+    }
+  }
+
+  fn expand_inverse_into(&self, moves: &mut Vec<Move>) {
+    let expanded = self.expand();
+    moves.extend(expanded.iter().rev().map(Move::inverse));
+  }
+}
+
+impl std::fmt::Display for AlgExpr {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      AlgExpr::Move(m) => write!(f, "{}", m),
+      AlgExpr::Seq(exprs) => {
+        for (i, e) in exprs.iter().enumerate() {
+          if i > 0 {
+            write!(f, " ")?;
+          }
+          write!(f, "{}", e)?;
+        }
+        Ok(())
+      }
+      AlgExpr::Paren(e) => write!(f, "({})", e),
+      AlgExpr::Bracket(e) => write!(f, "[{}]", e),
+      AlgExpr::Commutator(a, b) => write!(f, "[{}, {}]", a, b),
+      AlgExpr::Conjugate(a, b) => write!(f, "[{}: {}]", a, b),
+      AlgExpr::Repeat(e, n) => {
+        // Only groups can take a count, so add parentheses if needed.
+        match **e {
+          AlgExpr::Move(_) | AlgExpr::Seq(_) => write!(f, "({})", e)?,
+          _ => write!(f, "{}", e)?,
+        }
+        match n {
+          1 => Ok(()),
+          -1 => write!(f, "'"),
+          n if *n < 0 => write!(f, "{}'", -n),
+          n => write!(f, "{}", n),
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse_moves;
+
+  /// Make a sequence of the moves in `s`.
+  fn m(s: &str) -> Box<AlgExpr> {
+    Box::new(AlgExpr::Seq(
+      parse_moves(s)
+        .unwrap()
+        .into_iter()
+        .map(AlgExpr::Move)
+        .collect(),
+    ))
+  }
+
+  #[test]
+  fn expand() {
+    assert_eq!(
+      parse_moves("R U R' D R U' R' D'").unwrap(),
+      AlgExpr::Commutator(m("R U R'"), m("D")).expand()
+    );
+    assert_eq!(
+      parse_moves("F R U R' U' F'").unwrap(),
+      AlgExpr::Conjugate(m("F"), m("R U R' U'")).expand()
+    );
+    assert_eq!(
+      parse_moves("R U R U").unwrap(),
+      AlgExpr::Repeat(Box::new(AlgExpr::Paren(m("R U"))), 2).expand()
+    );
+    assert_eq!(
+      parse_moves("U' R' U' R'").unwrap(),
+      AlgExpr::Repeat(Box::new(AlgExpr::Paren(m("R U"))), -2).expand()
+    );
+    assert_eq!(Vec::<Move>::new(), AlgExpr::Repeat(m("R U"), 0).expand());
+  }
+
+  #[test]
+  fn expanded_len() {
+    let e = AlgExpr::Seq(vec![
+      AlgExpr::Conjugate(
+        m("R' U'"),
+        Box::new(AlgExpr::Commutator(m("R' D' R"), m("U2"))),
+      ),
+      AlgExpr::Repeat(Box::new(AlgExpr::Bracket(m("M' U"))), -4),
+    ]);
+    assert_eq!(e.expand().len(), e.expanded_len());
+
+    let huge = (0..4).fold(*m("R U"), |e, _| {
+      AlgExpr::Repeat(Box::new(AlgExpr::Paren(Box::new(e))), i32::MAX)
+    });
+    assert_eq!(usize::MAX, huge.expanded_len());
+  }
+
+  #[test]
+  fn display() {
+    let e = AlgExpr::Seq(vec![
+      AlgExpr::Conjugate(
+        m("R' U'"),
+        Box::new(AlgExpr::Commutator(m("R' D' R"), m("U2"))),
+      ),
+      AlgExpr::Repeat(Box::new(AlgExpr::Bracket(m("M' U"))), 4),
+      AlgExpr::Repeat(m("R U"), -3),
+    ]);
+    assert_eq!("[R' U': [R' D' R, U2]] [M' U]4 (R U)3'", e.to_string());
+  }
+}
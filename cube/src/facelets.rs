@@ -0,0 +1,211 @@
+use crate::sticker_cube::{Cube, Facelet};
+use crate::Face;
+
+/// The stickers of each face in facelet string order. The faces are in the
+/// order U, R, F, D, L, B and each one is read row by row, as it looks when
+/// unfolded into a net with F in the middle and B on the right.
+pub(crate) static FACELETS: [Facelet; 54] = {
+  use self::Facelet::*;
+  use crate::sticker_cube::CentrePos as Ce;
+  use crate::sticker_cube::CornerPos as C;
+  use crate::sticker_cube::EdgePos as E;
+  [
+    // U
+    Corner(C::ULB),
+    Edge(E::UB),
+    Corner(C::UBR),
+    Edge(E::UL),
+    Centre(Ce::U),
+    Edge(E::UR),
+    Corner(C::UFL),
+    Edge(E::UF),
+    Corner(C::URF),
+    // R
+    Corner(C::RFU),
+    Edge(E::RU),
+    Corner(C::RUB),
+    Edge(E::RF),
+    Centre(Ce::R),
+    Edge(E::RB),
+    Corner(C::RDF),
+    Edge(E::RD),
+    Corner(C::RBD),
+    // F
+    Corner(C::FLU),
+    Edge(E::FU),
+    Corner(C::FUR),
+    Edge(E::FL),
+    Centre(Ce::F),
+    Edge(E::FR),
+    Corner(C::FDL),
+    Edge(E::FD),
+    Corner(C::FRD),
+    // D
+    Corner(C::DLF),
+    Edge(E::DF),
+    Corner(C::DFR),
+    Edge(E::DL),
+    Centre(Ce::D),
+    Edge(E::DR),
+    Corner(C::DBL),
+    Edge(E::DB),
+    Corner(C::DRB),
+    // L
+    Corner(C::LBU),
+    Edge(E::LU),
+    Corner(C::LUF),
+    Edge(E::LB),
+    Centre(Ce::L),
+    Edge(E::LF),
+    Corner(C::LDB),
+    Edge(E::LD),
+    Corner(C::LFD),
+    // B
+    Corner(C::BRU),
+    Edge(E::BU),
+    Corner(C::BUL),
+    Edge(E::BR),
+    Centre(Ce::B),
+    Edge(E::BL),
+    Corner(C::BDR),
+    Edge(E::BD),
+    Corner(C::BLD),
+  ]
+};
+
+/// The reason a facelet string could not be read, see `Cube::from_facelets`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FaceletsError {
+  /// The string does not have 54 facelets. This holds the number it has.
+  Length(usize),
+  /// A facelet is not one of `URFDLB`. This holds its index and character.
+  InvalidChar(usize, char),
+}
+
+impl std::fmt::Display for FaceletsError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      FaceletsError::Length(n) => write!(f, "expected 54 facelets, got {}", n),
+      FaceletsError::InvalidChar(i, c) => {
+        write!(f, "invalid facelet '{}' at position {}", c, i)
+      }
+    }
+  }
+}
+
+impl std::error::Error for FaceletsError {}
+
+impl Cube {
+  /// Get the sticker at a position.
+  pub fn facelet(&self, f: Facelet) -> Face {
+    match f {
+      Facelet::Edge(e) => self.edges[e as usize],
+      Facelet::Corner(c) => self.corners[c as usize],
+      Facelet::Centre(c) => self.centres[c as usize],
+    }
+  }
+
+  /// Create a cube from a 54 character facelet string, as used by Kociemba's
+  /// solver and most other cube programs. The faces are in the order U, R,
+  /// F, D, L, B, and each letter names the face whose solved centre has
+  /// that sticker. The centres are read from the string, so the cube can be
+  /// rotated. The result may not be a valid cube, see `Cube::validate`.
+  pub fn from_facelets(s: &str) -> Result<Cube, FaceletsError> {
+    let n = s.chars().count();
+    if n != FACELETS.len() {
+      return Err(FaceletsError::Length(n));
+    }
+
+    let mut c = Cube::invalid();
+    for (i, (ch, &f)) in s.chars().zip(FACELETS.iter()).enumerate() {
+      let face = match ch {
+        'U' => Face::U,
+        'R' => Face::R,
+        'F' => Face::F,
+        'D' => Face::D,
+        'L' => Face::L,
+        'B' => Face::B,
+        _ => return Err(FaceletsError::InvalidChar(i, ch)),
+      };
+      match f {
+        Facelet::Edge(e) => c.edges[e as usize] = face,
+        Facelet::Corner(p) => c.corners[p as usize] = face,
+        Facelet::Centre(p) => c.centres[p as usize] = face,
+      }
+    }
+    Ok(c)
+  }
+
+  /// Get the 54 character facelet string of the cube, see
+  /// `Cube::from_facelets`.
+  pub fn to_facelets(&self) -> String {
+    FACELETS
+      .iter()
+      .map(|&f| self.facelet(f).to_string())
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SOLVED: &str = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+
+  #[test]
+  fn solved() {
+    assert_eq!(SOLVED, Cube::solved().to_facelets());
+    assert_eq!(Ok(Cube::solved()), Cube::from_facelets(SOLVED));
+  }
+
+  #[test]
+  fn moves() {
+    let cases = [
+      (
+        "R",
+        "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB",
+      ),
+      (
+        "U",
+        "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB",
+      ),
+      (
+        "F",
+        "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB",
+      ),
+      (
+        "x",
+        "FFFFFFFFFRRRRRRRRRDDDDDDDDDBBBBBBBBBLLLLLLLLLUUUUUUUUU",
+      ),
+    ];
+    for (moves, facelets) in cases.iter() {
+      let c = Cube::from_moves(moves).unwrap();
+      assert_eq!(*facelets, c.to_facelets(), "{}", moves);
+      assert_eq!(Ok(c), Cube::from_facelets(facelets));
+    }
+  }
+
+  #[test]
+  fn round_trip() {
+    let c = Cube::from_moves(
+      "D2 F2 U' B2 R2 U F2 L2 U' R' B' L F' D L' U' F U2 R B M y",
+    )
+    .unwrap();
+    assert_eq!(Ok(c), Cube::from_facelets(&c.to_facelets()));
+
+    let s = "DRLUUBFBRBLURRLRUBLRDDFDLFUFUFFDBRDUBRUFLLFDDBFLUBLRBD";
+    assert_eq!(s, Cube::from_facelets(s).unwrap().to_facelets());
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(
+      Err(FaceletsError::Length(53)),
+      Cube::from_facelets(&SOLVED[1..])
+    );
+    assert_eq!(
+      Err(FaceletsError::InvalidChar(4, 'W')),
+      Cube::from_facelets(&SOLVED.replacen("UUUUU", "UUUUW", 1))
+    );
+  }
+}
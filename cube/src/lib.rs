@@ -1,8 +1,24 @@
+mod alg;
+pub mod cubie_cube;
+mod expr;
+mod facelets;
+mod mask;
 mod parser;
+pub mod render;
+pub mod scramble;
 pub mod sticker_cube;
-
-pub use crate::parser::parse_moves;
+pub mod svg;
+mod validate;
+
+pub use crate::alg::Alg;
+pub use crate::cubie_cube::CubieCube;
+pub use crate::expr::AlgExpr;
+pub use crate::facelets::FaceletsError;
+pub use crate::mask::Mask;
+pub use crate::parser::{parse_alg, parse_moves, ParseError, ParseErrorKind};
+pub use crate::scramble::Scramble;
 pub use crate::sticker_cube::Cube;
+pub use crate::validate::{CubeError, PieceErrors};
 
 /// Represents a face of the cube.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -88,13 +104,17 @@ pub enum Move {
   Face(Face, u8),
   Slice(Slice, u8),
   Rotation(Rotation, u8),
+  Wide(Face, u8),
 }
 
 impl Move {
   /// Get the amount of 90 degree turns. Returns 1, 2 or 3.
   pub fn amount(&self) -> u8 {
     match self {
-      Move::Face(_, a) | Move::Slice(_, a) | Move::Rotation(_, a) => *a,
+      Move::Face(_, a)
+      | Move::Slice(_, a)
+      | Move::Rotation(_, a)
+      | Move::Wide(_, a) => *a,
     }
   }
 
@@ -104,9 +124,15 @@ impl Move {
       Move::Face(f, _) => Move::Face(*f, amount),
       Move::Slice(s, _) => Move::Slice(*s, amount),
       Move::Rotation(s, _) => Move::Rotation(*s, amount),
+      Move::Wide(f, _) => Move::Wide(*f, amount),
     }
   }
 
+  /// Get the move that undoes `self`.
+  pub fn inverse(&self) -> Move {
+    self.with_amount(4 - self.amount())
+  }
+
   /// Compare `m` to `self`, returning true if they have the same 'movement'.
   /// This essentially compares the moves, but ignoring the amount field.
   pub fn is_same_movement(&self, m: &Move) -> bool {
@@ -127,6 +153,12 @@ impl std::fmt::Display for Move {
       Move::Rotation(slice, amt) => {
         write!(f, "{}{}", slice, amounts[*amt as usize - 1])
       }
+      Move::Wide(face, amt) => write!(
+        f,
+        "{}{}",
+        face.to_string().to_lowercase(),
+        amounts[*amt as usize - 1]
+      ),
     }
   }
 }
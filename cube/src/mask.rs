@@ -0,0 +1,240 @@
+use crate::sticker_cube::{CentrePos, CornerPos, Cube, EdgePos, Facelet};
+use crate::Face;
+use std::sync::OnceLock;
+
+/// Which piece of the solved cube has each set of stickers, indexed by
+/// `Face as usize`. Edges can be in either order and corners in any
+/// rotation.
+struct Pieces {
+  edges: [[Option<u8>; 6]; 6],
+  corners: [[[Option<u8>; 6]; 6]; 6],
+  centres: [u8; 6],
+}
+
+fn pieces() -> &'static Pieces {
+  static PIECES: OnceLock<Pieces> = OnceLock::new();
+  PIECES.get_or_init(|| {
+    let solved = Cube::solved();
+    let mut pieces = Pieces {
+      edges: [[None; 6]; 6],
+      corners: [[[None; 6]; 6]; 6],
+      centres: [0; 6],
+    };
+    for (j, s) in solved.edges.chunks(2).enumerate() {
+      pieces.edges[s[0] as usize][s[1] as usize] = Some(j as u8);
+      pieces.edges[s[1] as usize][s[0] as usize] = Some(j as u8);
+    }
+    for (j, s) in solved.corners.chunks(3).enumerate() {
+      for k in 0..3 {
+        let (a, b, c) = (s[k], s[(k + 1) % 3], s[(k + 2) % 3]);
+        pieces.corners[a as usize][b as usize][c as usize] = Some(j as u8);
+      }
+    }
+    for (j, &f) in solved.centres.iter().enumerate() {
+      pieces.centres[f as usize] = j as u8;
+    }
+    pieces
+  })
+}
+
+/// A set of pieces that matter, such as the pieces solved by a step of a
+/// method. The stickers of every other piece are masked, so they are drawn in
+/// grey and match any sticker when comparing cubes.
+///
+/// Pieces are named by where they are on a solved cube, and are found by
+/// their stickers, so a mask follows its pieces around the cube.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mask {
+  edges: [bool; 12],
+  corners: [bool; 8],
+  centres: [bool; 6],
+}
+
+impl Mask {
+  /// Create a mask that keeps the given pieces.
+  pub fn new(
+    edges: &[EdgePos],
+    corners: &[CornerPos],
+    centres: &[CentrePos],
+  ) -> Mask {
+    let mut mask = Mask::none();
+    for &e in edges {
+      mask.edges[e as usize / 2] = true;
+    }
+    for &c in corners {
+      mask.corners[c as usize / 3] = true;
+    }
+    for &c in centres {
+      mask.centres[c as usize] = true;
+    }
+    mask
+  }
+
+  /// Create a mask that keeps every piece.
+  pub fn all() -> Mask {
+    Mask {
+      edges: [true; 12],
+      corners: [true; 8],
+      centres: [true; 6],
+    }
+  }
+
+  /// Create a mask that hides every piece.
+  pub fn none() -> Mask {
+    Mask {
+      edges: [false; 12],
+      corners: [false; 8],
+      centres: [false; 6],
+    }
+  }
+
+  /// The Roux first block: DL, FL, BL, DLF, DBL and the L centre.
+  pub fn first_block() -> Mask {
+    use crate::sticker_cube::CentrePos as Ce;
+    use crate::sticker_cube::CornerPos as C;
+    use crate::sticker_cube::EdgePos as E;
+    Mask::new(&[E::DL, E::FL, E::BL], &[C::DLF, C::DBL], &[Ce::L])
+  }
+
+  /// The Roux first and second blocks.
+  pub fn first_two_blocks() -> Mask {
+    use crate::sticker_cube::CentrePos as Ce;
+    use crate::sticker_cube::CornerPos as C;
+    use crate::sticker_cube::EdgePos as E;
+    Mask::first_block().union(&Mask::new(
+      &[E::DR, E::FR, E::BR],
+      &[C::DFR, C::DRB],
+      &[Ce::R],
+    ))
+  }
+
+  /// The Roux first two blocks and the U layer corners, which is everything
+  /// except the last six edges and the M slice centres.
+  pub fn f2b_cmll() -> Mask {
+    use crate::sticker_cube::CornerPos as C;
+    Mask::first_two_blocks().union(&Mask::new(
+      &[],
+      &[C::URF, C::UFL, C::ULB, C::UBR],
+      &[],
+    ))
+  }
+
+  /// Get a mask that keeps the pieces kept by either `self` or `other`.
+  pub fn union(&self, other: &Mask) -> Mask {
+    let mut mask = *self;
+    mask
+      .edges
+      .iter_mut()
+      .zip(&other.edges)
+      .for_each(|(a, b)| *a |= b);
+    mask
+      .corners
+      .iter_mut()
+      .zip(&other.corners)
+      .for_each(|(a, b)| *a |= b);
+    mask
+      .centres
+      .iter_mut()
+      .zip(&other.centres)
+      .for_each(|(a, b)| *a |= b);
+    mask
+  }
+
+  /// Get the sticker at each facelet of `c`, in facelet order, or `None` if
+  /// the piece it belongs to is masked. Stickers that do not make up a real
+  /// piece are also masked.
+  pub fn stickers(&self, c: &Cube) -> [Option<Face>; 54] {
+    // Find whether the piece in each position is kept, before going through
+    // its facelets.
+    let pieces = pieces();
+    let mut edges = [false; 12];
+    for (i, keep) in edges.iter_mut().enumerate() {
+      let (a, b) = (c.edges[2 * i], c.edges[2 * i + 1]);
+      *keep = pieces.edges[a as usize][b as usize]
+        .is_some_and(|j| self.edges[j as usize]);
+    }
+    let mut corners = [false; 8];
+    for (i, keep) in corners.iter_mut().enumerate() {
+      let s = &c.corners[3 * i..3 * i + 3];
+      *keep = pieces.corners[s[0] as usize][s[1] as usize][s[2] as usize]
+        .is_some_and(|j| self.corners[j as usize]);
+    }
+
+    let mut stickers = [None; 54];
+    for (s, &f) in stickers.iter_mut().zip(Facelet::natural_order().iter()) {
+      let keep = match f {
+        Facelet::Edge(e) => edges[e as usize / 2],
+        Facelet::Corner(p) => corners[p as usize / 3],
+        Facelet::Centre(p) => {
+          self.centres[pieces.centres[c.centres[p as usize] as usize] as usize]
+        }
+      };
+      if keep {
+        *s = Some(c.facelet(f));
+      }
+    }
+    stickers
+  }
+
+  /// Test if `a` and `b` have the same stickers, treating masked stickers as
+  /// wildcards. A masked sticker only matches another masked sticker, so a
+  /// kept piece must be in the same place on both cubes.
+  pub fn matches(&self, a: &Cube, b: &Cube) -> bool {
+    self.stickers(a)[..] == self.stickers(b)[..]
+  }
+
+  /// Test if the pieces kept by the mask are solved.
+  pub fn is_solved(&self, c: &Cube) -> bool {
+    self.matches(c, &Cube::solved())
+  }
+}
+
+impl Default for Mask {
+  fn default() -> Self {
+    Mask::all()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn stickers() {
+    let solved = Cube::solved();
+    assert!(Mask::all().stickers(&solved).iter().all(Option::is_some));
+    assert!(Mask::none().stickers(&solved).iter().all(Option::is_none));
+
+    let count =
+      |m: &Mask, c: &Cube| m.stickers(c).iter().filter(|s| s.is_some()).count();
+    assert_eq!(3 * 2 + 2 * 3 + 1, count(&Mask::first_block(), &solved));
+    assert_eq!(26, count(&Mask::first_two_blocks(), &solved));
+    assert_eq!(38, count(&Mask::f2b_cmll(), &solved));
+
+    // The mask follows the pieces.
+    let c = Cube::from_moves("L' U2 F' R").unwrap();
+    assert_eq!(13, count(&Mask::first_block(), &c));
+    assert_eq!(0, count(&Mask::first_block(), &Cube::invalid()));
+  }
+
+  #[test]
+  fn matches() {
+    let fb = Mask::first_block();
+    assert!(fb.is_solved(&Cube::from_moves("R U M' r' U2 M2").unwrap()));
+    assert!(!fb.is_solved(&Cube::from_moves("L").unwrap()));
+    assert!(!fb.is_solved(&Cube::from_moves("x").unwrap()));
+
+    let f2b = Mask::first_two_blocks();
+    assert!(f2b.is_solved(&Cube::from_moves("M2 U M' U2").unwrap()));
+    assert!(!f2b.is_solved(&Cube::from_moves("R U R' U'").unwrap()));
+    let r = Cube::from_moves("R").unwrap();
+    assert!(f2b.matches(&r, &Cube::from_moves("R M").unwrap()));
+    assert!(!f2b.matches(&r, &Cube::from_moves("R'").unwrap()));
+
+    let cmll = Mask::f2b_cmll();
+    assert!(cmll.is_solved(&Cube::from_moves("M2 U2 M2 U2").unwrap()));
+    assert!(!cmll.is_solved(&Cube::from_moves("R U R' U R U2 R'").unwrap()));
+    assert!(Mask::all().matches(&r, &r));
+    assert!(!Mask::all().matches(&r, &Cube::from_moves("M").unwrap()));
+  }
+}
@@ -1,64 +1,301 @@
 use nom::types::CompleteStr as Input;
 use nom::*;
 
-use crate::{Face, Move, Rotation};
+use std::ops::Range;
+
+use crate::{AlgExpr, Face, Move, Rotation, Slice};
+
+fn face(ly: char) -> Face {
+  match ly.to_ascii_uppercase() {
+    'U' => Face::U,
+    'F' => Face::F,
+    'R' => Face::R,
+    'L' => Face::L,
+    'B' => Face::B,
+    'D' => Face::D,
+    _ => unreachable!(),
+  }
+}
+
+/// A wide move of all three layers is a rotation.
+fn wide3(f: Face) -> Move {
+  match f {
+    Face::R => Move::Rotation(Rotation::X, 1),
+    Face::L => Move::Rotation(Rotation::X, 3),
+    Face::U => Move::Rotation(Rotation::Y, 1),
+    Face::D => Move::Rotation(Rotation::Y, 3),
+    Face::F => Move::Rotation(Rotation::Z, 1),
+    Face::B => Move::Rotation(Rotation::Z, 3),
+  }
+}
+
+// WCA style wide moves, e.g. Rw, 2Rw or 3Rw.
+named!(wide_w<Input, Move>,
+  do_parse!(
+    layers: opt!(one_of!("23")) >>
+    f: map!(one_of!("UFRLBD"), face) >>
+    char!('w') >>
+    (match layers {
+      Some('3') => wide3(f),
+      _ => Move::Wide(f, 1),
+    })
+  )
+);
 
 named!(mv<Input, Move>,
-  map!(
-    one_of!("UFRLBDMxy"),
-      |ly| match ly {
-        'U' => Move::Face(Face::U, 1),
-        'F' => Move::Face(Face::F, 1),
-        'R' => Move::Face(Face::R, 1),
-        'L' => Move::Face(Face::L, 1),
-        'B' => Move::Face(Face::B, 1),
-        'D' => Move::Face(Face::D, 1),
-        'M' => Move::Slice(crate::Slice::M, 1),
-        'x' => Move::Rotation(Rotation::X, 1),
-        'y' => Move::Rotation(Rotation::Y, 1),
-        _ => unreachable!()
-      }
+  alt!(
+    wide_w |
+    map!(
+      one_of!("UFRLBDMESxyzufrlbd"),
+        |ly| match ly {
+          'U' | 'F' | 'R' | 'L' | 'B' | 'D' => Move::Face(face(ly), 1),
+          'M' => Move::Slice(Slice::M, 1),
+          'E' => Move::Slice(Slice::E, 1),
+          'S' => Move::Slice(Slice::S, 1),
+          'x' => Move::Rotation(Rotation::X, 1),
+          'y' => Move::Rotation(Rotation::Y, 1),
+          'z' => Move::Rotation(Rotation::Z, 1),
+          _ => Move::Wide(face(ly), 1),
+        }
+    )
   )
 );
 
+// A repeat count, reduced modulo 4 so that large counts can't overflow.
+named!(count<Input, u8>,
+  map!(digit, |d| d.chars().fold(0, |acc, c| {
+    (acc * 10 + c.to_digit(10).unwrap() as u8) % 4
+  }))
+);
+
+// The amount of quarter turns, e.g. 2, ', 2' or 3. This can be 0, e.g. R4.
 named!(suffix<Input, u8>,
-  map!(
-    opt!(one_of!("'2")),
-      |or| match or {
-        Some('\'') => 3,
-        Some('2') => 2,
-        None => 1,
-        _ => unreachable!()
-      }
+  do_parse!(
+    n: opt!(count) >>
+    prime: opt!(one_of!("'’")) >>
+    ({
+      let n = n.unwrap_or(1);
+      if prime.is_some() { (4 - n) % 4 } else { n }
+    })
   )
 );
 
-named!(move_<Input, Move>,
+named!(move_<Input, Option<Move>>,
   do_parse!(
-    multispace0 >> m: mv >> amt: suffix >> (m.with_amount(amt))
+    m: mv >> amt: suffix >>
+    // `m` can have an amount of 3, e.g. 3Lw is x'.
+    ({
+      let amt = m.amount() * amt % 4;
+      if amt == 0 { None } else { Some(m.with_amount(amt)) }
+    })
   )
 );
 
-named!(moves<Input, Vec<Move>>,
-  many0!( move_ )
+named!(comment<Input, Input>,
+  preceded!(tag!("//"), take_till!(|c| c == '\n'))
+);
+
+named!(ws<Input, ()>,
+  map!(many0!(alt!(multispace | comment)), |_| ())
+);
+
+/// The largest count a group can be repeated.
+pub const MAX_REPEAT: i32 = 1000;
+
+/// The most moves an algorithm can expand to.
+pub const MAX_MOVES: usize = 100_000;
+
+// A count after a group, e.g. (R U)3 or [R, U]2'. A count larger than
+// MAX_REPEAT stops the whole parse.
+named!(repeat<Input, i32>,
+  do_parse!(
+    count: opt!(peek!(digit)) >>
+    n: cond!(
+      count.is_some(),
+      return_error!(
+        ErrorKind::Custom(0),
+        verify!(
+          map_res!(digit, |d: Input| d.0.parse::<i32>()),
+          |n: i32| n <= MAX_REPEAT
+        )
+      )
+    ) >>
+    prime: opt!(one_of!("'’")) >>
+    ({
+      let n = n.unwrap_or(1);
+      if prime.is_some() { -n } else { n }
+    })
+  )
+);
+
+// The contents of brackets, either a plain group, a commutator or a
+// conjugate.
+named!(bracket_body<Input, AlgExpr>,
+  do_parse!(
+    a: seq >>
+    b: opt!(pair!(preceded!(ws, one_of!(",:")), seq)) >>
+    (match b {
+      None => AlgExpr::Bracket(Box::new(a)),
+      Some((',', b)) => AlgExpr::Commutator(Box::new(a), Box::new(b)),
+      Some((_, b)) => AlgExpr::Conjugate(Box::new(a), Box::new(b)),
+    })
+  )
+);
+
+named!(group<Input, AlgExpr>,
+  do_parse!(
+    g: alt!(
+      delimited!(
+        char!('('),
+        map!(seq, |e| AlgExpr::Paren(Box::new(e))),
+        preceded!(ws, char!(')'))
+      ) |
+      delimited!(char!('['), bracket_body, preceded!(ws, char!(']')))
+    ) >>
+    n: repeat >>
+    (if n == 1 { g } else { AlgExpr::Repeat(Box::new(g), n) })
+  )
+);
+
+named!(item<Input, Option<AlgExpr>>,
+  preceded!(
+    ws,
+    alt!(map!(move_, |m| m.map(AlgExpr::Move)) | map!(group, Some))
+  )
 );
 
-pub fn parse_moves(data: &str) -> Result<Vec<Move>, String> {
-  let (etc, moves) = moves(Input(data)).expect("unknown parser error");
-  let etc = etc.to_string();
-  if !etc.is_empty() {
-    let pos = data.len() - etc.len();
-    Err(format!(
-      "{}\n{}^ parse error at position {}",
-      data,
-      String::from_utf8(vec![b' '; pos + 1]).unwrap(),
-      pos,
-    ))
-  } else {
-    Ok(moves)
+named!(seq<Input, AlgExpr>,
+  map!(
+    fold_many0!(item, Vec::new(), |mut acc: Vec<AlgExpr>, e| {
+      acc.extend(e);
+      acc
+    }),
+    AlgExpr::Seq
+  )
+);
+
+/// The kind of error found by `parse_alg` or `parse_moves`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseErrorKind {
+  /// A character that does not start a move, group or comment.
+  UnexpectedChar(char),
+  /// A `(` or `[` without a matching closing character.
+  UnclosedGroup(char),
+  /// A `)` or `]` without a matching opening character.
+  UnmatchedClose(char),
+  /// A group repeated more than `MAX_REPEAT` times.
+  CountTooLarge,
+  /// An algorithm that expands to more than `MAX_MOVES` moves.
+  TooManyMoves,
+}
+
+/// An error from `parse_alg` or `parse_moves`. `span` is the byte range of
+/// the input that caused the error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+  pub kind: ParseErrorKind,
+  pub span: Range<usize>,
+}
+
+impl std::fmt::Display for ParseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self.kind {
+      ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected '{}'", c),
+      ParseErrorKind::UnclosedGroup(c) => write!(f, "unclosed '{}'", c),
+      ParseErrorKind::UnmatchedClose(c) => write!(f, "unmatched '{}'", c),
+      ParseErrorKind::CountTooLarge => {
+        write!(f, "count larger than {}", MAX_REPEAT)
+      }
+      ParseErrorKind::TooManyMoves => {
+        write!(f, "more than {} moves", MAX_MOVES)
+      }
+    }?;
+    write!(f, " at position {}", self.span.start)
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+fn skip_ws(i: &str) -> &str {
+  match ws(Input(i)) {
+    Ok((rest, _)) => rest.0,
+    Err(_) => i,
+  }
+}
+
+/// Work out why parsing stopped at `rest`, which is a suffix of `data`.
+fn diagnose(data: &str, rest: &str) -> ParseError {
+  let rest = skip_ws(rest);
+  let pos = data.len() - rest.len();
+  let c = rest.chars().next().unwrap_or(' ');
+  let kind = match c {
+    '(' | '[' => {
+      // The group failed, so find the error inside of it.
+      let inner = &rest[1..];
+      let body = if c == '(' {
+        seq(Input(inner))
+      } else {
+        bracket_body(Input(inner))
+      };
+      let inner_rest = match body {
+        Ok((inner_rest, _)) => skip_ws(inner_rest.0),
+        Err(_) => inner,
+      };
+      if !inner_rest.is_empty() {
+        return diagnose(data, inner_rest);
+      }
+      return ParseError {
+        kind: ParseErrorKind::UnclosedGroup(c),
+        span: pos..data.len(),
+      };
+    }
+    ')' | ']' => ParseErrorKind::UnmatchedClose(c),
+    _ => ParseErrorKind::UnexpectedChar(c),
+  };
+  ParseError {
+    kind,
+    span: pos..pos + c.len_utf8(),
   }
 }
 
+/// Parse an algorithm in standard notation, keeping its structure.
+///
+/// Parentheses and brackets can be used to group moves, and `//` starts a
+/// comment that runs to the end of the line. Brackets can also hold a
+/// commutator, `[A, B]`, or a conjugate, `[A: B]`. Groups can be followed by
+/// a count, e.g. `(R U R' U')3`, of at most `MAX_REPEAT`. The whole
+/// algorithm can expand to at most `MAX_MOVES` moves.
+pub fn parse_alg(data: &str) -> Result<AlgExpr, ParseError> {
+  match terminated!(Input(data), seq, ws) {
+    Ok((rest, alg)) if rest.is_empty() => {
+      if alg.expanded_len() > MAX_MOVES {
+        return Err(ParseError {
+          kind: ParseErrorKind::TooManyMoves,
+          span: 0..data.len(),
+        });
+      }
+      Ok(alg)
+    }
+    Ok((rest, _)) => Err(diagnose(data, rest.0)),
+    // Only a count that is too large stops the parse like this.
+    Err(Err::Failure(Context::Code(rest, _))) => {
+      let start = data.len() - rest.len();
+      let digits = rest.chars().take_while(char::is_ascii_digit).count();
+      Err(ParseError {
+        kind: ParseErrorKind::CountTooLarge,
+        span: start..start + digits,
+      })
+    }
+    Err(_) => Err(diagnose(data, data)),
+  }
+}
+
+/// Parse a sequence of moves in standard notation. See `parse_alg` for the
+/// accepted notation, this expands it into a flat list of moves.
+pub fn parse_moves(data: &str) -> Result<Vec<Move>, ParseError> {
+  parse_alg(data).map(|alg| alg.expand())
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -78,9 +315,242 @@ mod tests {
     );
   }
 
+  #[test]
+  fn wide() {
+    assert_eq!(vec![Move::Wide(Face::R, 1)], parse_moves("r").unwrap());
+    assert_eq!(vec![Move::Wide(Face::R, 1)], parse_moves("Rw").unwrap());
+    assert_eq!(vec![Move::Wide(Face::R, 3)], parse_moves("2Rw'").unwrap());
+    assert_eq!(
+      vec![
+        Move::Wide(Face::U, 2),
+        Move::Face(Face::R, 1),
+        Move::Wide(Face::F, 3)
+      ],
+      parse_moves("u2 R Fw'").unwrap()
+    );
+    assert_eq!(
+      vec![
+        Move::Rotation(Rotation::X, 1),
+        Move::Rotation(Rotation::X, 3)
+      ],
+      parse_moves("3Rw 3Lw").unwrap()
+    );
+    assert_eq!(
+      vec![
+        Move::Rotation(Rotation::Y, 1),
+        Move::Rotation(Rotation::Z, 2)
+      ],
+      parse_moves("3Dw' 3Bw2").unwrap()
+    );
+    assert_eq!(
+      "r2 b'",
+      format!("{} {}", Move::Wide(Face::R, 2), Move::Wide(Face::B, 3))
+    );
+  }
+
+  #[test]
+  fn slices_and_rotations() {
+    assert_eq!(
+      vec![
+        Move::Slice(Slice::E, 1),
+        Move::Slice(Slice::S, 3),
+        Move::Rotation(Rotation::Z, 2),
+        Move::Rotation(Rotation::Y, 3),
+        Move::Rotation(Rotation::X, 2)
+      ],
+      parse_moves("E S' z2 y' x2'").unwrap()
+    );
+  }
+
+  #[test]
+  fn counts() {
+    assert_eq!(vec![Move::Face(Face::R, 3)], parse_moves("R3").unwrap());
+    assert_eq!(vec![Move::Face(Face::R, 2)], parse_moves("R2'").unwrap());
+    assert_eq!(vec![Move::Face(Face::R, 1)], parse_moves("R3'").unwrap());
+    assert_eq!(vec![Move::Face(Face::R, 1)], parse_moves("R5").unwrap());
+    assert_eq!(vec![Move::Face(Face::R, 3)], parse_moves("R’").unwrap());
+    assert_eq!(
+      vec![Move::Face(Face::U, 1)],
+      parse_moves("R4 U R0 R99999999999999999999999999999996").unwrap()
+    );
+  }
+
+  #[test]
+  fn groups_and_comments() {
+    let expected = parse_moves("R U R' U' D R' F R").unwrap();
+    assert_eq!(expected, parse_moves("(R U R' U') [D R'] F R").unwrap());
+    assert_eq!(expected, parse_moves("(R U (R' U')) D R' [F R]").unwrap());
+    assert_eq!(
+      expected,
+      parse_moves(
+        "R U R' U' // sexy move
+         D R' F R // finish
+        "
+      )
+      .unwrap()
+    );
+    assert_eq!(
+      Vec::<Move>::new(),
+      parse_moves("  // only a comment").unwrap()
+    );
+  }
+
+  #[test]
+  fn commutators() {
+    let expand = |s| parse_moves(s).unwrap();
+
+    assert_eq!(expand("R U R' D R U' R' D'"), expand("[R U R', D]"));
+    assert_eq!(expand("F R U R' U' F'"), expand("[F: R U R' U']"));
+    assert_eq!(expand("R U R' U' R U R' U'"), expand("(R U R' U')2"));
+    assert_eq!(expand("U R U' R' U R U' R'"), expand("(R U R' U')2'"));
+    assert_eq!(expand("M' U M' U M' U M' U"), expand("[M' U]4"));
+    assert_eq!(
+      expand("R' U' R' D' R U2 R' D R U2 U R"),
+      expand("[R' U': [R' D' R, U2]]")
+    );
+    assert_eq!(expand("R U R' U' R U' R' U"), expand("[R, U] [R, U']"));
+    assert_eq!(expand("U D U' D'"), expand("[U, D]1 ()3 [R, U]0"));
+  }
+
+  #[test]
+  fn alg_display() {
+    for &s in &[
+      "[R U R', D]",
+      "[F: R U R' U']",
+      "(R U R' U')3",
+      "[R' U': [R' D' R, U2]]",
+      "y' [M' U]4 // comment",
+      "r U (R' U')2' [M: U]",
+    ] {
+      let alg = parse_alg(s).unwrap();
+      assert_eq!(alg, parse_alg(&alg.to_string()).unwrap());
+    }
+    assert_eq!(
+      "[R U R', D] (R U)2",
+      parse_alg("[ R U R' ,D ]  (R U)2 // comment")
+        .unwrap()
+        .to_string()
+    );
+  }
+
   #[test]
   fn invalid() {
     assert!(parse_moves("Foo").is_err());
-    assert!(parse_moves("F3").is_err());
+    assert!(parse_moves("F'3").is_err());
+    assert!(parse_moves("4Rw").is_err());
+    assert!(parse_moves("Mw").is_err());
+  }
+
+  #[test]
+  fn errors() {
+    use ParseErrorKind::*;
+
+    let err = |s| parse_moves(s).unwrap_err();
+
+    assert_eq!(
+      ParseError {
+        kind: UnexpectedChar('o'),
+        span: 1..2
+      },
+      err("Foo")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnexpectedChar('Q'),
+        span: 5..6
+      },
+      err("R U  Q")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnclosedGroup('('),
+        span: 2..7
+      },
+      err("R (U R'")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnexpectedChar('q'),
+        span: 8..9
+      },
+      err("R (U [R q])")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnmatchedClose(']'),
+        span: 5..6
+      },
+      err("(R U ] ")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnmatchedClose(')'),
+        span: 4..5
+      },
+      err("R U ) R")
+    );
+    assert_eq!("unclosed '[' at position 0", err("[R").to_string());
+    assert_eq!(
+      ParseError {
+        kind: UnclosedGroup('['),
+        span: 2..10
+      },
+      err("U [R, U D ")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnexpectedChar(':'),
+        span: 5..6
+      },
+      err("[R, U: D]")
+    );
+    assert_eq!(
+      ParseError {
+        kind: UnexpectedChar(','),
+        span: 2..3
+      },
+      err("(R, U)")
+    );
+  }
+
+  #[test]
+  fn limits() {
+    use ParseErrorKind::*;
+
+    let err = |s| parse_moves(s).unwrap_err();
+
+    assert_eq!(1000, parse_moves("(R)1000").unwrap().len());
+    assert_eq!(
+      ParseError {
+        kind: CountTooLarge,
+        span: 5..15
+      },
+      err("(R U)2000000000")
+    );
+    assert_eq!(
+      ParseError {
+        kind: CountTooLarge,
+        span: 8..34
+      },
+      err("U [R, U]99999999999999999999999999'")
+    );
+    assert_eq!(
+      ParseError {
+        kind: CountTooLarge,
+        span: 6..10
+      },
+      err("((R U)1001)2")
+    );
+    assert_eq!(
+      ParseError {
+        kind: TooManyMoves,
+        span: 0..23
+      },
+      err("(((R U)1000)1000)1000 R")
+    );
+    assert_eq!(
+      "count larger than 1000 at position 5",
+      err("(R U)2000000000").to_string()
+    );
   }
 }
@@ -0,0 +1,227 @@
+use crate::facelets::FACELETS;
+use crate::sticker_cube::{CentrePos, Cube};
+use crate::{Alg, Face, Mask};
+
+/// The colour of a sticker.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colour {
+  White,
+  Yellow,
+  Green,
+  Blue,
+  Red,
+  Orange,
+  /// Used for stickers that are hidden.
+  Grey,
+}
+
+impl Colour {
+  /// Get the first letter of the colour's name, or `X` for grey.
+  pub fn letter(self) -> char {
+    match self {
+      Colour::White => 'W',
+      Colour::Yellow => 'Y',
+      Colour::Green => 'G',
+      Colour::Blue => 'B',
+      Colour::Red => 'R',
+      Colour::Orange => 'O',
+      Colour::Grey => 'X',
+    }
+  }
+
+  /// Get the colour as a hex RGB string, e.g. `#ffffff`.
+  pub fn hex(self) -> &'static str {
+    match self {
+      Colour::White => "#ffffff",
+      Colour::Yellow => "#ffd500",
+      Colour::Green => "#009b48",
+      Colour::Blue => "#0046ad",
+      Colour::Red => "#b71234",
+      Colour::Orange => "#ff5800",
+      Colour::Grey => "#808080",
+    }
+  }
+
+  /// Get the ANSI 256 colour number for the colour.
+  fn ansi(self) -> u8 {
+    match self {
+      Colour::White => 231,
+      Colour::Yellow => 226,
+      Colour::Green => 34,
+      Colour::Blue => 21,
+      Colour::Red => 196,
+      Colour::Orange => 208,
+      Colour::Grey => 244,
+    }
+  }
+}
+
+/// The colour of each face's stickers, in `CentrePos` order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColourScheme(pub [Colour; 6]);
+
+impl ColourScheme {
+  /// The usual scheme, with white on U and green on F.
+  pub fn western() -> ColourScheme {
+    use self::Colour::*;
+    ColourScheme([White, Red, Green, Yellow, Blue, Orange])
+  }
+
+  /// Get the colour of the stickers that belong to `f`.
+  pub fn colour(&self, f: Face) -> Colour {
+    let pos = match f {
+      Face::U => CentrePos::U,
+      Face::R => CentrePos::R,
+      Face::F => CentrePos::F,
+      Face::D => CentrePos::D,
+      Face::B => CentrePos::B,
+      Face::L => CentrePos::L,
+    };
+    self.0[pos as usize]
+  }
+}
+
+impl Default for ColourScheme {
+  fn default() -> Self {
+    ColourScheme::western()
+  }
+}
+
+/// Options for `Cube::net`.
+#[derive(Clone, Debug, Default)]
+pub struct NetOptions {
+  pub scheme: ColourScheme,
+  /// Moves, usually rotations, done to the cube before drawing it. For
+  /// example `x2` draws the cube with the D face on top.
+  pub rotation: Alg,
+  /// Pieces to draw, the others are drawn grey.
+  pub mask: Mask,
+  /// Draw the stickers with ANSI colours instead of letters.
+  pub ansi: bool,
+}
+
+/// The row and column of each face in the net, in facelet order.
+pub(crate) const NET_FACES: [(usize, usize); 6] =
+  [(0, 1), (1, 2), (1, 1), (2, 1), (1, 0), (1, 3)];
+
+/// Get the colour of each sticker in facelet order, after doing `rotation`
+/// to the cube. Masked stickers are grey.
+pub(crate) fn colours(
+  c: &Cube,
+  rotation: &Alg,
+  mask: &Mask,
+  scheme: &ColourScheme,
+) -> Vec<Colour> {
+  let mut c = *c;
+  c.do_moves(rotation);
+  mask
+    .stickers(&c)
+    .iter()
+    .map(|s| s.map_or(Colour::Grey, |f| scheme.colour(f)))
+    .collect()
+}
+
+/// Draw a net of `stickers`, which are in facelet order, using `draw` to draw
+/// each sticker and `blank` for the gaps, which must be the same width.
+fn draw_net<T: Copy, F: Fn(T) -> String>(
+  stickers: &[T],
+  blank: &str,
+  draw: F,
+) -> String {
+  let mut grid = vec![vec![None; 12]; 9];
+  for (i, &s) in stickers.iter().enumerate() {
+    let (row, col) = NET_FACES[i / 9];
+    grid[row * 3 + i % 9 / 3][col * 3 + i % 3] = Some(s);
+  }
+
+  let mut net = String::new();
+  for row in grid {
+    let len = row.iter().rposition(Option::is_some).unwrap() + 1;
+    for s in &row[..len] {
+      match s {
+        Some(s) => net.push_str(&draw(*s)),
+        None => net.push_str(blank),
+      }
+    }
+    net.push('\n');
+  }
+  net
+}
+
+impl Cube {
+  /// Draw the cube as an unfolded net, with U on top, L, F, R and B in the
+  /// middle and D on the bottom.
+  pub fn net(&self, options: &NetOptions) -> String {
+    let colours =
+      colours(self, &options.rotation, &options.mask, &options.scheme);
+    if options.ansi {
+      draw_net(&colours, "  ", |c| {
+        format!("\x1b[48;5;{}m  \x1b[0m", c.ansi())
+      })
+    } else {
+      draw_net(&colours, " ", |c| c.letter().to_string())
+    }
+  }
+}
+
+impl std::fmt::Display for Cube {
+  /// Draw the net of the cube using the face names of the stickers.
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let stickers: Vec<Face> =
+      FACELETS.iter().map(|&f| self.facelet(f)).collect();
+    write!(f, "{}", draw_net(&stickers, " ", |f| f.to_string()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse_moves;
+
+  #[test]
+  fn display() {
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R").unwrap());
+    let expected = concat!(
+      "   UUF\n",
+      "   UUF\n",
+      "   UUF\n",
+      "LLLFFDRRRUBB\n",
+      "LLLFFDRRRUBB\n",
+      "LLLFFDRRRUBB\n",
+      "   DDB\n",
+      "   DDB\n",
+      "   DDB\n",
+    );
+    assert_eq!(expected, c.to_string());
+  }
+
+  #[test]
+  fn net() {
+    let options = NetOptions {
+      rotation: parse_moves("x2").unwrap().into(),
+      ..Default::default()
+    };
+    let net = Cube::solved().net(&options);
+    assert_eq!("   YYY\n", &net[..7]);
+    assert_eq!(Some("OOOBBBRRRGGG"), net.lines().nth(3));
+
+    let options = NetOptions {
+      ansi: true,
+      ..Default::default()
+    };
+    let net = Cube::solved().net(&options);
+    let white = "\x1b[48;5;231m  \x1b[0m";
+    assert!(net.starts_with(&format!("      {}{}{}\n", white, white, white)));
+    assert_eq!(9, net.lines().count());
+
+    let options = NetOptions {
+      mask: Mask::first_block(),
+      ..Default::default()
+    };
+    let net = Cube::solved().net(&options);
+    assert_eq!(Some("XXXXXXXXXXXX"), net.lines().nth(3));
+    assert_eq!(Some("OOOGXXXXXXXB"), net.lines().nth(4));
+    assert_eq!(Some("   YXX"), net.lines().nth(8));
+  }
+}
@@ -0,0 +1,273 @@
+use crate::validate::parity;
+use crate::{parse_moves, Alg, Cube, CubieCube, Move};
+use rand::Rng;
+
+/// A random cube state and a move sequence that reaches it from solved. The
+/// moves are found by solving one piece at a time, so they are a lot longer
+/// than an optimal solution.
+#[derive(Clone, Debug)]
+pub struct Scramble {
+  pub cube: Cube,
+  pub moves: Alg,
+}
+
+/// Create a scramble for a uniformly random legal state.
+pub fn random_state<R: Rng + ?Sized>(rng: &mut R) -> Scramble {
+  random_pieces(
+    rng,
+    &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    &[0, 1, 2, 3, 4, 5, 6, 7],
+  )
+}
+
+/// Create a scramble with the first two layers solved and a uniformly random
+/// last layer.
+pub fn random_last_layer<R: Rng + ?Sized>(rng: &mut R) -> Scramble {
+  random_pieces(rng, &[0, 1, 2, 3], &[0, 1, 2, 3])
+}
+
+/// Create a scramble with the first two blocks solved, for practicing CMLL.
+/// The U layer corners and the six edges of the last six edges step are
+/// uniformly random.
+pub fn random_cmll<R: Rng + ?Sized>(rng: &mut R) -> Scramble {
+  random_pieces(rng, &[0, 1, 2, 3, 4, 6], &[0, 1, 2, 3])
+}
+
+/// Create a scramble with the first block (DL, FL, BL, DLF and DBL) solved
+/// and every other piece uniformly random.
+pub fn random_first_block<R: Rng + ?Sized>(rng: &mut R) -> Scramble {
+  random_pieces(rng, &[0, 1, 2, 3, 4, 6, 7, 8, 11], &[0, 1, 2, 3, 4, 7])
+}
+
+/// Create a scramble for a uniformly random legal state where only the pieces
+/// at the given `CubieCube` edge and corner positions are moved.
+pub fn random_pieces<R: Rng + ?Sized>(
+  rng: &mut R,
+  edges: &[u8],
+  corners: &[u8],
+) -> Scramble {
+  let mut cc = CubieCube::solved();
+
+  shuffle(rng, edges, &mut cc.ep);
+  shuffle(rng, corners, &mut cc.cp);
+  if parity(&cc.cp) != parity(&cc.ep) {
+    if edges.len() >= 2 {
+      cc.ep.swap(edges[0] as usize, edges[1] as usize);
+    } else {
+      cc.cp.swap(corners[0] as usize, corners[1] as usize);
+    }
+  }
+
+  twist(rng, edges, 2, &mut cc.eo);
+  twist(rng, corners, 3, &mut cc.co);
+
+  let moves = Alg(solve(cc)).inverse().cancel();
+  Scramble {
+    cube: cc.to_cube(),
+    moves,
+  }
+}
+
+/// Shuffle the pieces at `positions` amongst themselves.
+fn shuffle<R: Rng + ?Sized>(rng: &mut R, positions: &[u8], p: &mut [u8]) {
+  for i in (1..positions.len()).rev() {
+    let j = rng.gen_range(0..=i);
+    p.swap(positions[i] as usize, positions[j] as usize);
+  }
+}
+
+/// Randomly orient the pieces at `positions`, keeping the total orientation a
+/// multiple of `n`.
+fn twist<R: Rng + ?Sized>(rng: &mut R, positions: &[u8], n: u8, o: &mut [u8]) {
+  if let Some((&last, rest)) = positions.split_last() {
+    let mut total = 0;
+    for &i in rest {
+      o[i as usize] = rng.gen_range(0..n);
+      total += o[i as usize];
+    }
+    o[last as usize] = (n - total % n) % n;
+  }
+}
+
+// The pieces are solved one at a time by swapping them with a buffer, using a
+// setup, a swap and the inverse of the setup. The edge swap also swaps two
+// corners, and the corner swap also swaps two edges. Solving the edges first
+// means an even number of corner swaps are needed, so their edge swaps cancel.
+
+/// Swaps the UR and UL edges, and the UBR and URF corners.
+const EDGE_SWAP: &str = "R U R' U' R' F R2 U' R' U' R U R' F'";
+const EDGE_BUFFER: usize = 3;
+
+/// Setups that bring each edge to UL, indexed by `2 * position + flip`.
+static EDGE_SETUPS: [&str; 24] = [
+  "R2 U R2",
+  "R F' R' L'",
+  "",
+  "U B U' L",
+  "R2 U' R2",
+  "R' B R L",
+  "",
+  "",
+  "D' L2",
+  "F L' F'",
+  "L2",
+  "F' D F L'",
+  "D L2",
+  "B' L B",
+  "D2 L2",
+  "F' D' F L'",
+  "U2 R U2",
+  "U' F' U",
+  "L'",
+  "U' F U",
+  "L",
+  "U B' U'",
+  "U2 R' U2",
+  "U B U'",
+];
+
+/// Swaps the ULB and URF corners, and the UL and UB edges.
+const CORNER_SWAP: &str = "F R U' R' U' R U R' F' R U R' U' R' F R F'";
+const CORNER_BUFFER: usize = 2;
+
+/// Setups that bring each corner to URF, indexed by `3 * position + twist`.
+static CORNER_SETUPS: [&str; 24] = [
+  "", "R' F'", "F R", "F R' F'", "F2 R", "F", "", "", "", "R D' F'", "R'",
+  "R2 F'", "D R2", "R", "F'", "F2", "D R", "F' R", "D F2", "D2 R", "D2 F'",
+  "R2", "R F'", "D' F'",
+];
+
+/// Get the moves that solve `cc`, which must be a legal state.
+fn solve(mut cc: CubieCube) -> Vec<Move> {
+  let mut moves = Vec::new();
+  let mut swap = |cc: &mut CubieCube, setup: &str, swap: &str| {
+    let setup = Alg(parse_moves(setup).unwrap());
+    let alg =
+      [&setup[..], &parse_moves(swap).unwrap(), &setup.inverse()].concat();
+    cc.do_moves(&alg);
+    moves.extend(alg);
+  };
+
+  loop {
+    let p = cc.ep[EDGE_BUFFER] as usize;
+    let target = if p != EDGE_BUFFER {
+      2 * p + cc.eo[EDGE_BUFFER] as usize
+    } else {
+      // Move the buffer piece into an unsolved position, to solve that next.
+      let unsolved = |&i: &usize| cc.ep[i] as usize != i || cc.eo[i] != 0;
+      match (0..12).filter(|&i| i != EDGE_BUFFER).find(unsolved) {
+        Some(i) => 2 * i,
+        None => break,
+      }
+    };
+    swap(&mut cc, EDGE_SETUPS[target], EDGE_SWAP);
+  }
+
+  loop {
+    let p = cc.cp[CORNER_BUFFER] as usize;
+    let target = if p != CORNER_BUFFER {
+      3 * p + (3 - cc.co[CORNER_BUFFER] as usize) % 3
+    } else {
+      let unsolved = |&i: &usize| cc.cp[i] as usize != i || cc.co[i] != 0;
+      match (0..8).filter(|&i| i != CORNER_BUFFER).find(unsolved) {
+        Some(i) => 3 * i,
+        None => break,
+      }
+    };
+    swap(&mut cc, CORNER_SETUPS[target], CORNER_SWAP);
+  }
+
+  debug_assert_eq!(CubieCube::solved(), cc);
+  moves
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::rngs::SmallRng;
+  use rand::SeedableRng;
+
+  fn check(s: &Scramble, edges: &[u8], corners: &[u8]) {
+    assert_eq!(Ok(()), s.cube.validate());
+
+    let mut c = Cube::solved();
+    c.do_moves(&s.moves);
+    assert_eq!(s.cube, c);
+
+    let cc = CubieCube::from_cube(&s.cube).unwrap();
+    for i in 0..12 {
+      if !edges.contains(&i) {
+        assert_eq!((i, 0), (cc.ep[i as usize], cc.eo[i as usize]));
+      }
+    }
+    for i in 0..8 {
+      if !corners.contains(&i) {
+        assert_eq!((i, 0), (cc.cp[i as usize], cc.co[i as usize]));
+      }
+    }
+  }
+
+  fn conjugate(setup: &str, swap: &str) -> CubieCube {
+    let setup = Alg(parse_moves(setup).unwrap());
+    let mut cc = CubieCube::solved();
+    cc.do_moves(&setup);
+    cc.do_moves(&parse_moves(swap).unwrap());
+    cc.do_moves(&setup.inverse());
+    cc
+  }
+
+  #[test]
+  fn setups() {
+    for (k, setup) in EDGE_SETUPS.iter().enumerate() {
+      let (i, flip) = (k / 2, k as u8 % 2);
+      if i == EDGE_BUFFER {
+        continue;
+      }
+      let mut expected = conjugate("", EDGE_SWAP);
+      expected.ep = CubieCube::solved().ep;
+      expected.ep.swap(i, EDGE_BUFFER);
+      expected.eo[i] = flip;
+      expected.eo[EDGE_BUFFER] = flip;
+      assert_eq!(expected, conjugate(setup, EDGE_SWAP), "{}", k);
+    }
+
+    for (k, setup) in CORNER_SETUPS.iter().enumerate() {
+      let (i, twist) = (k / 3, k as u8 % 3);
+      if i == CORNER_BUFFER {
+        continue;
+      }
+      let mut expected = conjugate("", CORNER_SWAP);
+      expected.cp = CubieCube::solved().cp;
+      expected.cp.swap(i, CORNER_BUFFER);
+      expected.co[i] = twist;
+      expected.co[CORNER_BUFFER] = (3 - twist) % 3;
+      assert_eq!(expected, conjugate(setup, CORNER_SWAP), "{}", k);
+    }
+  }
+
+  #[test]
+  fn variants() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let all = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+    for _ in 0..20 {
+      check(&random_state(&mut rng), &all, &all);
+      check(&random_last_layer(&mut rng), &[0, 1, 2, 3], &[0, 1, 2, 3]);
+      check(&random_cmll(&mut rng), &[0, 1, 2, 3, 4, 6], &[0, 1, 2, 3]);
+      check(
+        &random_first_block(&mut rng),
+        &[0, 1, 2, 3, 4, 6, 7, 8, 11],
+        &[0, 1, 2, 3, 4, 7],
+      );
+      check(&random_pieces(&mut rng, &[0], &[0, 1]), &[0], &[0, 1]);
+    }
+  }
+
+  #[test]
+  fn seeded() {
+    let a = random_state(&mut SmallRng::seed_from_u64(42));
+    let b = random_state(&mut SmallRng::seed_from_u64(42));
+    let c = random_state(&mut SmallRng::seed_from_u64(43));
+    assert_eq!(a.moves, b.moves);
+    assert_ne!(a.moves, c.moves);
+  }
+}
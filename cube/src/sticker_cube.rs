@@ -1,4 +1,4 @@
-use crate::{Face, Move, Rotation, Slice};
+use crate::{parse_moves, Face, Move, ParseError, Rotation, Slice};
 
 /// Represents a 3x3x3 cube using a representation that is similar to storing
 /// sticker colours. This representation includes centre pieces so can
@@ -32,6 +32,13 @@ impl Cube {
     }
   }
 
+  /// Creates a cube by doing `moves`, e.g. `"R U R' U'"`, on a solved cube.
+  pub fn from_moves(moves: &str) -> Result<Cube, ParseError> {
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves(moves)?);
+    Ok(c)
+  }
+
   /// Creates a cube in an invalid state.
   pub fn invalid() -> Cube {
     use crate::Face::*;
@@ -67,13 +74,15 @@ impl Cube {
         },
         Move::Slice(m, ..) => match m {
           Slice::M => self.do_m(),
-          _ => unimplemented!("Slice {:?} not implemented!", m),
+          Slice::E => self.do_e(),
+          Slice::S => self.do_s(),
         },
         Move::Rotation(r, ..) => match r {
           Rotation::X => self.do_x(),
           Rotation::Y => self.do_y(),
-          _ => unimplemented!("Rotation {:?} not implemented!", r),
+          Rotation::Z => self.do_z(),
         },
+        Move::Wide(f, ..) => self.do_wide(*f),
       }
     }
   }
@@ -175,25 +184,143 @@ impl Cube {
     self.centres[CentrePos::R as usize] = centres[CentrePos::F as usize];
   }
 
+  fn do_s(&mut self) {
+    use self::EdgePos::*;
+    edge4(UL, RU, DR, LD, &mut self.edges);
+    edge4(LU, UR, RD, DL, &mut self.edges);
+
+    let centres = self.centres;
+    self.centres[CentrePos::R as usize] = centres[CentrePos::U as usize];
+    self.centres[CentrePos::D as usize] = centres[CentrePos::R as usize];
+    self.centres[CentrePos::L as usize] = centres[CentrePos::D as usize];
+    self.centres[CentrePos::U as usize] = centres[CentrePos::L as usize];
+  }
+
   pub fn do_x(&mut self) {
-    // FIXME: Use direct cycles.
-    self.do_r();
-    for _ in 0..3 {
-      self.do_m();
-      self.do_l();
-    }
+    use self::EdgePos::*;
+    // R
+    edge4(UR, BR, DR, FR, &mut self.edges);
+    edge4(RU, RB, RD, RF, &mut self.edges);
+    // M'
+    edge4(UF, BU, DB, FD, &mut self.edges);
+    edge4(FU, UB, BD, DF, &mut self.edges);
+    // L'
+    edge4(UL, BL, DL, FL, &mut self.edges);
+    edge4(LU, LB, LD, LF, &mut self.edges);
+
+    use self::CornerPos::*;
+    // R
+    corner4(URF, BRU, DRB, FRD, &mut self.corners);
+    corner4(RFU, RUB, RBD, RDF, &mut self.corners);
+    corner4(FUR, UBR, BDR, DFR, &mut self.corners);
+    // L'
+    corner4(UFL, BUL, DBL, FDL, &mut self.corners);
+    corner4(FLU, ULB, BLD, DLF, &mut self.corners);
+    corner4(LUF, LBU, LDB, LFD, &mut self.corners);
+
+    let centres = self.centres;
+    self.centres[CentrePos::U as usize] = centres[CentrePos::F as usize];
+    self.centres[CentrePos::B as usize] = centres[CentrePos::U as usize];
+    self.centres[CentrePos::D as usize] = centres[CentrePos::B as usize];
+    self.centres[CentrePos::F as usize] = centres[CentrePos::D as usize];
   }
 
   pub fn do_y(&mut self) {
-    // FIXME: Use direct cycles.
-    self.do_u();
-    for _ in 0..3 {
-      self.do_e();
-      self.do_d();
+    use self::EdgePos::*;
+    // U
+    edge4(UF, UL, UB, UR, &mut self.edges);
+    edge4(FU, LU, BU, RU, &mut self.edges);
+    // E'
+    edge4(FR, LF, BL, RB, &mut self.edges);
+    edge4(RF, FL, LB, BR, &mut self.edges);
+    // D'
+    edge4(DF, DL, DB, DR, &mut self.edges);
+    edge4(FD, LD, BD, RD, &mut self.edges);
+
+    use self::CornerPos::*;
+    // U
+    corner4(URF, UFL, ULB, UBR, &mut self.corners);
+    corner4(RFU, FLU, LBU, BRU, &mut self.corners);
+    corner4(FUR, LUF, BUL, RUB, &mut self.corners);
+    // D'
+    corner4(DFR, DLF, DBL, DRB, &mut self.corners);
+    corner4(FRD, LFD, BLD, RBD, &mut self.corners);
+    corner4(RDF, FDL, LDB, BDR, &mut self.corners);
+
+    let centres = self.centres;
+    self.centres[CentrePos::F as usize] = centres[CentrePos::R as usize];
+    self.centres[CentrePos::L as usize] = centres[CentrePos::F as usize];
+    self.centres[CentrePos::B as usize] = centres[CentrePos::L as usize];
+    self.centres[CentrePos::R as usize] = centres[CentrePos::B as usize];
+  }
+
+  pub fn do_z(&mut self) {
+    use self::EdgePos::*;
+    // F
+    edge4(UF, RF, DF, LF, &mut self.edges);
+    edge4(FU, FR, FD, FL, &mut self.edges);
+    // S
+    edge4(UL, RU, DR, LD, &mut self.edges);
+    edge4(LU, UR, RD, DL, &mut self.edges);
+    // B'
+    edge4(UB, RB, DB, LB, &mut self.edges);
+    edge4(BU, BR, BD, BL, &mut self.edges);
+
+    use self::CornerPos::*;
+    // F
+    corner4(URF, RDF, DLF, LUF, &mut self.corners);
+    corner4(RFU, DFR, LFD, UFL, &mut self.corners);
+    corner4(FUR, FRD, FDL, FLU, &mut self.corners);
+    // B'
+    corner4(UBR, RBD, DBL, LBU, &mut self.corners);
+    corner4(BRU, BDR, BLD, BUL, &mut self.corners);
+    corner4(RUB, DRB, LDB, ULB, &mut self.corners);
+
+    let centres = self.centres;
+    self.centres[CentrePos::R as usize] = centres[CentrePos::U as usize];
+    self.centres[CentrePos::D as usize] = centres[CentrePos::R as usize];
+    self.centres[CentrePos::L as usize] = centres[CentrePos::D as usize];
+    self.centres[CentrePos::U as usize] = centres[CentrePos::L as usize];
+  }
+
+  /// A wide move turns the face and the adjacent slice in the same direction.
+  fn do_wide(&mut self, f: Face) {
+    match f {
+      Face::U => {
+        self.do_u();
+        for _ in 0..3 {
+          self.do_e();
+        }
+      }
+      Face::D => {
+        self.do_d();
+        self.do_e();
+      }
+      Face::F => {
+        self.do_f();
+        self.do_s();
+      }
+      Face::B => {
+        self.do_b();
+        for _ in 0..3 {
+          self.do_s();
+        }
+      }
+      Face::R => {
+        self.do_r();
+        for _ in 0..3 {
+          self.do_m();
+        }
+      }
+      Face::L => {
+        self.do_l();
+        self.do_m();
+      }
     }
   }
 
   /// Find the `EdgePos` for a particular edge piece.
+  /// Panics if the edge is not on the cube, see `Cube::validate`.
   pub fn find_edge(&self, f1: Face, f2: Face) -> EdgePos {
     let edge_pos = EdgePos::natural_order();
     for (e, ep) in self.edges.chunks(2).zip(edge_pos.chunks(2)) {
@@ -208,6 +335,7 @@ impl Cube {
   }
 
   /// Find the `CornerPos` for a particular corner piece.
+  /// Panics if the corner is not on the cube, see `Cube::validate`.
   pub fn find_corner(&self, f1: Face, f2: Face, f3: Face) -> CornerPos {
     let corner_pos = CornerPos::natural_order();
     for (c, cp) in self.corners.chunks(3).zip(corner_pos.chunks(3)) {
@@ -263,8 +391,7 @@ fn corner4(
 
 /// Represents a particular edge position on a cube.
 /// Note: This represents a position, not a particular piece.
-#[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum EdgePos {
   UF,
   FU,
@@ -299,14 +426,13 @@ impl EdgePos {
       UF, FU, UL, LU, UB, BU, UR, RU, DF, FD, DL, LD, DB, BD, DR, RD, FR, RF,
       FL, LF, BL, LB, BR, RB,
     ];
-    return &EDGES;
+    &EDGES
   }
 }
 
 /// Represents a particular corner position on a cube.
 /// Note: This represents a position, not a particular piece.
-#[derive(Clone, Copy, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CornerPos {
   URF,
   RFU,
@@ -341,13 +467,13 @@ impl CornerPos {
       URF, RFU, FUR, UFL, FLU, LUF, ULB, LBU, BUL, UBR, BRU, RUB, DFR, FRD,
       RDF, DLF, LFD, FDL, DBL, BLD, LDB, DRB, RBD, BDR,
     ];
-    return &CORNERS;
+    &CORNERS
   }
 }
 
 /// Represents a particular centre position on a cube.
 /// Note: This represents a position, not a particular piece.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CentrePos {
   U,
   R,
@@ -357,6 +483,22 @@ pub enum CentrePos {
   L,
 }
 
+/// Represents a particular sticker position on a cube.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Facelet {
+  Edge(EdgePos),
+  Corner(CornerPos),
+  Centre(CentrePos),
+}
+
+impl Facelet {
+  /// Get every facelet, in the order of a facelet string, see
+  /// `Cube::from_facelets`.
+  pub fn natural_order() -> &'static [Facelet; 54] {
+    &crate::facelets::FACELETS
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::Face::*;
@@ -589,6 +731,48 @@ mod tests {
     );
   }
 
+  #[test]
+  fn e_move() {
+    let mut c = Cube::solved();
+    c.do_e();
+
+    assert_eq!(
+      Cube {
+        edges: [
+          U, F, U, L, U, B, U, R, D, F, D, L, D, B, D, R, L, F, L, B, R, B, R,
+          F
+        ],
+        corners: [
+          U, R, F, U, F, L, U, L, B, U, B, R, D, F, R, D, L, F, D, B, L, D, R,
+          B
+        ],
+        centres: [U, F, L, D, R, B]
+      },
+      c
+    );
+  }
+
+  #[test]
+  fn s_move() {
+    let mut c = Cube::solved();
+    c.do_s();
+
+    assert_eq!(
+      Cube {
+        edges: [
+          U, F, L, D, U, B, L, U, D, F, R, D, D, B, R, U, F, R, F, L, B, L, B,
+          R
+        ],
+        corners: [
+          U, R, F, U, F, L, U, L, B, U, B, R, D, F, R, D, L, F, D, B, L, D, R,
+          B
+        ],
+        centres: [L, U, F, R, B, D]
+      },
+      c
+    );
+  }
+
   #[test]
   fn x_move() {
     let mut c = Cube::solved();
@@ -631,6 +815,114 @@ mod tests {
     );
   }
 
+  #[test]
+  fn z_move() {
+    let mut c = Cube::solved();
+    c.do_z();
+
+    assert_eq!(
+      Cube {
+        edges: [
+          L, F, L, D, L, B, L, U, R, F, R, D, R, B, R, U, F, U, F, D, B, D, B,
+          U
+        ],
+        corners: [
+          L, U, F, L, F, D, L, D, B, L, B, U, R, F, U, R, D, F, R, B, D, R, U,
+          B
+        ],
+        centres: [L, U, F, R, B, D],
+      },
+      c
+    );
+  }
+
+  #[test]
+  fn slices_and_rotations() {
+    use crate::Move::*;
+
+    // E = y' U D'
+    let mut c = Cube::solved();
+    c.do_move(Slice(crate::Slice::E, 1));
+    let mut c2 = Cube::solved();
+    c2.do_moves(&[Rotation(crate::Rotation::Y, 3), Face(U, 1), Face(D, 3)]);
+    assert_eq!(c2, c);
+
+    // S = z F' B
+    let mut c = Cube::solved();
+    c.do_move(Slice(crate::Slice::S, 1));
+    let mut c2 = Cube::solved();
+    c2.do_moves(&[Rotation(crate::Rotation::Z, 1), Face(F, 3), Face(B, 1)]);
+    assert_eq!(c2, c);
+
+    // z = x y x'
+    let mut c = Cube::solved();
+    c.do_move(Rotation(crate::Rotation::Z, 1));
+    let mut c2 = Cube::solved();
+    c2.do_moves(&[
+      Rotation(crate::Rotation::X, 1),
+      Rotation(crate::Rotation::Y, 1),
+      Rotation(crate::Rotation::X, 3),
+    ]);
+    assert_eq!(c2, c);
+  }
+
+  #[test]
+  fn wide_moves() {
+    use crate::Move::*;
+    use crate::Rotation::*;
+
+    // Each wide move matches the rotation combined with the opposite face.
+    for &(w, r, f) in &[
+      (U, Y, D),
+      (D, Y, U),
+      (F, Z, B),
+      (B, Z, F),
+      (R, X, L),
+      (L, X, R),
+    ] {
+      let r_amt = if w == D || w == B || w == L { 3 } else { 1 };
+      let mut c = Cube::solved();
+      c.do_move(Wide(w, 1));
+      let mut c2 = Cube::solved();
+      c2.do_moves(&[Rotation(r, r_amt), Face(f, 1)]);
+      assert_eq!(c2, c);
+    }
+  }
+
+  #[test]
+  fn all_moves() {
+    let moves = [
+      Move::Face(U, 1),
+      Move::Face(D, 1),
+      Move::Face(F, 1),
+      Move::Face(B, 1),
+      Move::Face(R, 1),
+      Move::Face(L, 1),
+      Move::Slice(crate::Slice::M, 1),
+      Move::Slice(crate::Slice::E, 1),
+      Move::Slice(crate::Slice::S, 1),
+      Move::Rotation(crate::Rotation::X, 1),
+      Move::Rotation(crate::Rotation::Y, 1),
+      Move::Rotation(crate::Rotation::Z, 1),
+      Move::Wide(U, 1),
+      Move::Wide(D, 1),
+      Move::Wide(F, 1),
+      Move::Wide(B, 1),
+      Move::Wide(R, 1),
+      Move::Wide(L, 1),
+    ];
+
+    for &m in &moves {
+      for amt in 1..4 {
+        let mut c = Cube::solved();
+        c.do_move(m.with_amount(amt));
+        assert_ne!(Cube::solved(), c);
+        c.do_move(m.with_amount(4 - amt));
+        assert_eq!(Cube::solved(), c);
+      }
+    }
+  }
+
   #[test]
   fn moves() {
     let mut c2 = Cube::solved();
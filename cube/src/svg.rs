@@ -0,0 +1,212 @@
+use crate::render::{colours, Colour, ColourScheme, NET_FACES};
+use crate::sticker_cube::Cube;
+use crate::{Alg, Mask};
+use std::fmt::Write;
+
+/// Options for drawing a cube as an SVG image.
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+  pub scheme: ColourScheme,
+  /// Moves, usually rotations, done to the cube before drawing it.
+  pub rotation: Alg,
+  /// Pieces to draw, the others are drawn grey.
+  pub mask: Mask,
+  /// The width of the image in pixels. The height follows from the view.
+  pub width: u32,
+}
+
+impl Default for SvgOptions {
+  fn default() -> Self {
+    SvgOptions {
+      scheme: ColourScheme::default(),
+      rotation: Alg::default(),
+      mask: Mask::all(),
+      width: 200,
+    }
+  }
+}
+
+/// Wrap the shapes in `body` in an SVG document that shows the area `view`,
+/// given as `(x, y, width, height)`.
+fn document(view: (f32, f32, f32, f32), width: u32, body: &str) -> String {
+  let (x, y, w, h) = view;
+  let height = (width as f32 * h / w).round();
+  format!(
+    concat!(
+      "<svg xmlns=\"http://www.w3.org/2000/svg\" ",
+      "viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+      "{}</svg>\n"
+    ),
+    x, y, w, h, width, height, body
+  )
+}
+
+/// Add a sticker with the corners `points` to `body`.
+fn polygon(body: &mut String, points: &[(f32, f32)], colour: Colour) {
+  body.push_str("<polygon points=\"");
+  for (i, (x, y)) in points.iter().enumerate() {
+    if i > 0 {
+      body.push(' ');
+    }
+    write!(body, "{:.3},{:.3}", x, y).unwrap();
+  }
+  writeln!(
+    body,
+    "\" fill=\"{}\" stroke=\"#000\" stroke-width=\"0.04\" />",
+    colour.hex()
+  )
+  .unwrap();
+}
+
+/// Add a rectangular sticker to `body`.
+fn rect(body: &mut String, x: f32, y: f32, w: f32, h: f32, colour: Colour) {
+  polygon(
+    body,
+    &[(x, y), (x + w, y), (x + w, y + h), (x, y + h)],
+    colour,
+  );
+}
+
+/// Draw a 3D view of the U, F and R faces of the cube.
+pub fn isometric(c: &Cube, options: &SvgOptions) -> String {
+  let colours = colours(c, &options.rotation, &options.mask, &options.scheme);
+
+  // The cube fills 0..3 on each axis, with x going right, y going up and z
+  // going towards the front.
+  let project = |(x, y, z): (f32, f32, f32)| {
+    let cos30 = 3f32.sqrt() / 2.0;
+    ((x - z) * cos30, (x + z) / 2.0 - y)
+  };
+
+  // Each face's first facelet in facelet order, its top left corner, and the
+  // directions of its rows and columns.
+  type Point = (f32, f32, f32);
+  let faces: [(usize, Point, Point, Point); 3] = [
+    (0, (0.0, 3.0, 0.0), (1.0, 0.0, 0.0), (0.0, 0.0, 1.0)),
+    (9, (3.0, 3.0, 3.0), (0.0, 0.0, -1.0), (0.0, -1.0, 0.0)),
+    (18, (0.0, 3.0, 3.0), (1.0, 0.0, 0.0), (0.0, -1.0, 0.0)),
+  ];
+
+  let mut body = String::new();
+  for &(start, origin, right, down) in &faces {
+    for i in 0..9 {
+      let corner = |r: f32, c: f32| {
+        project((
+          origin.0 + c * right.0 + r * down.0,
+          origin.1 + c * right.1 + r * down.1,
+          origin.2 + c * right.2 + r * down.2,
+        ))
+      };
+      let (r, c) = ((i / 3) as f32, (i % 3) as f32);
+      let points = [
+        corner(r, c),
+        corner(r, c + 1.0),
+        corner(r + 1.0, c + 1.0),
+        corner(r + 1.0, c),
+      ];
+      polygon(&mut body, &points, colours[start + i]);
+    }
+  }
+
+  let (w, h) = (3.0 * 3f32.sqrt(), 6.0);
+  document(
+    (-w / 2.0 - 0.1, -h / 2.0 - 0.1, w + 0.2, h + 0.2),
+    options.width,
+    &body,
+  )
+}
+
+/// Draw the cube as an unfolded net, laid out like `Cube::net`.
+pub fn net(c: &Cube, options: &SvgOptions) -> String {
+  let colours = colours(c, &options.rotation, &options.mask, &options.scheme);
+  let mut body = String::new();
+  for (i, &colour) in colours.iter().enumerate() {
+    let (row, col) = NET_FACES[i / 9];
+    let x = (col * 3 + i % 3) as f32;
+    let y = (row * 3 + i % 9 / 3) as f32;
+    rect(&mut body, x, y, 1.0, 1.0, colour);
+  }
+  document((-0.1, -0.1, 12.2, 9.2), options.width, &body)
+}
+
+/// Draw the U face from above, with the top row of stickers of the F, R, B
+/// and L faces around it. This is the usual view for last layer cases.
+pub fn last_layer(c: &Cube, options: &SvgOptions) -> String {
+  let colours = colours(c, &options.rotation, &options.mask, &options.scheme);
+  // The width of the side stickers.
+  let t = 0.4;
+
+  let mut body = String::new();
+  for (i, &colour) in colours[..9].iter().enumerate() {
+    let (r, c) = ((i / 3) as f32, (i % 3) as f32);
+    rect(&mut body, t + c, t + r, 1.0, 1.0, colour);
+  }
+  for i in 0..3 {
+    let c = i as f32;
+    // F and R go from the front right corner, B and L from the back left.
+    rect(&mut body, t + c, t + 3.0, 1.0, t, colours[18 + i]);
+    rect(&mut body, t + 3.0, t + 2.0 - c, t, 1.0, colours[9 + i]);
+    rect(&mut body, t + 2.0 - c, 0.0, 1.0, t, colours[45 + i]);
+    rect(&mut body, 0.0, t + c, t, 1.0, colours[36 + i]);
+  }
+
+  let size = 3.0 + 2.0 * t;
+  document((-0.1, -0.1, size + 0.2, size + 0.2), options.width, &body)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse_moves;
+
+  fn fills(svg: &str, colour: Colour) -> usize {
+    svg.matches(&format!("fill=\"{}\"", colour.hex())).count()
+  }
+
+  #[test]
+  fn isometric_view() {
+    let svg = isometric(&Cube::solved(), &SvgOptions::default());
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(27, svg.matches("<polygon").count());
+    assert_eq!(9, fills(&svg, Colour::White));
+    assert_eq!(9, fills(&svg, Colour::Green));
+    assert_eq!(9, fills(&svg, Colour::Red));
+
+    let options = SvgOptions {
+      rotation: parse_moves("x2").unwrap().into(),
+      ..Default::default()
+    };
+    let svg = isometric(&Cube::solved(), &options);
+    assert_eq!(9, fills(&svg, Colour::Yellow));
+    assert_eq!(9, fills(&svg, Colour::Blue));
+  }
+
+  #[test]
+  fn net_view() {
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R").unwrap());
+    let svg = net(&c, &SvgOptions::default());
+    assert_eq!(54, svg.matches("<polygon").count());
+    for &colour in &[Colour::White, Colour::Green, Colour::Yellow] {
+      assert_eq!(9, fills(&svg, colour));
+    }
+    // The first sticker is U1, at the top left of the U face.
+    let first = svg.lines().nth(1).unwrap();
+    assert!(first.starts_with("<polygon points=\"3.000,0.000 4.000,0.000"));
+  }
+
+  #[test]
+  fn last_layer_view() {
+    let options = SvgOptions {
+      mask: Mask::f2b_cmll(),
+      ..Default::default()
+    };
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R U R' U R U2 R'").unwrap());
+    let svg = last_layer(&c, &options);
+    assert_eq!(21, svg.matches("<polygon").count());
+    // The U centre, the four U edges and their side stickers.
+    assert_eq!(9, fills(&svg, Colour::Grey));
+  }
+}
@@ -0,0 +1,271 @@
+use crate::sticker_cube::{CornerPos, Cube, EdgePos};
+use crate::{CubieCube, Face};
+
+/// Problems with the pieces of a `Cube`, see `CubeError::Pieces`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PieceErrors {
+  /// Positions whose stickers do not make up any corner piece. The
+  /// `CornerPos` is the first facelet of the position.
+  pub invalid_corners: Vec<CornerPos>,
+  /// Positions holding a corner piece that is also at an earlier position.
+  pub duplicate_corners: Vec<CornerPos>,
+  /// Corner pieces, named by their stickers, that are not on the cube.
+  pub missing_corners: Vec<(Face, Face, Face)>,
+  /// Positions whose stickers do not make up any edge piece. The `EdgePos`
+  /// is the first facelet of the position.
+  pub invalid_edges: Vec<EdgePos>,
+  /// Positions holding an edge piece that is also at an earlier position.
+  pub duplicate_edges: Vec<EdgePos>,
+  /// Edge pieces, named by their stickers, that are not on the cube.
+  pub missing_edges: Vec<(Face, Face)>,
+}
+
+/// The reason a `Cube` is not in a state that can be reached by turning a
+/// real cube.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CubeError {
+  /// The centres are not an orientation of a real cube.
+  Centres,
+  /// Some pieces are invalid, missing or duplicated.
+  Pieces(Box<PieceErrors>),
+  /// The corners are twisted. This holds the total twist, 1 means that
+  /// twisting a single corner anti-clockwise would fix it.
+  TwistedCorners(u8),
+  /// An odd number of edges are flipped.
+  FlippedEdges,
+  /// The corner and edge permutations have different parity, for example
+  /// two edges are swapped.
+  Parity,
+}
+
+impl std::fmt::Display for CubeError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      CubeError::Centres => write!(f, "invalid centres"),
+      CubeError::Pieces(_) => write!(f, "invalid, missing or duplicate pieces"),
+      CubeError::TwistedCorners(_) => write!(f, "twisted corners"),
+      CubeError::FlippedEdges => write!(f, "flipped edges"),
+      CubeError::Parity => write!(f, "permutation parity"),
+    }
+  }
+}
+
+impl std::error::Error for CubeError {}
+
+/// Get the parity of a permutation, 0 for even and 1 for odd.
+pub(crate) fn parity(p: &[u8]) -> u8 {
+  let mut parity = 0;
+  for i in 0..p.len() {
+    for j in (i + 1)..p.len() {
+      if p[i] > p[j] {
+        parity ^= 1;
+      }
+    }
+  }
+  parity
+}
+
+impl Cube {
+  /// Check that the cube is in a state that can be reached by turning a real
+  /// cube.
+  pub fn validate(&self) -> Result<(), CubeError> {
+    let solved = Cube::solved();
+    let centre = |f: Face| {
+      self.centres[solved.centres.iter().position(|&c| c == f).unwrap()]
+    };
+
+    // The centres must be a rotation of the solved centres, so opposite
+    // centres stay opposite and the U, R and F centres go around a corner in
+    // the same direction.
+    let (u, r, f) = (centre(Face::U), centre(Face::R), centre(Face::F));
+    let opposites = [Face::U, Face::R, Face::F]
+      .iter()
+      .all(|&c| centre(c).opposite() == centre(c.opposite()));
+    let handed = solved.corners.chunks(3).any(|c| {
+      (0..3).any(|k| (c[k], c[(k + 1) % 3], c[(k + 2) % 3]) == (u, r, f))
+    });
+    if !opposites || !handed {
+      return Err(CubeError::Centres);
+    }
+
+    // Find each piece by the stickers it should have relative to the centres.
+    let mut errors = PieceErrors::default();
+
+    let mut corners_seen = [false; 8];
+    for (i, c) in self.corners.chunks(3).enumerate() {
+      let j = solved.corners.chunks(3).position(|s| {
+        let s = (centre(s[0]), centre(s[1]), centre(s[2]));
+        (0..3).any(|k| (c[k], c[(k + 1) % 3], c[(k + 2) % 3]) == s)
+      });
+      match j {
+        None => errors
+          .invalid_corners
+          .push(CornerPos::natural_order()[3 * i]),
+        Some(j) if corners_seen[j] => errors
+          .duplicate_corners
+          .push(CornerPos::natural_order()[3 * i]),
+        Some(j) => corners_seen[j] = true,
+      }
+    }
+    for (j, s) in solved.corners.chunks(3).enumerate() {
+      if !corners_seen[j] {
+        errors
+          .missing_corners
+          .push((centre(s[0]), centre(s[1]), centre(s[2])));
+      }
+    }
+
+    let mut edges_seen = [false; 12];
+    for (i, e) in self.edges.chunks(2).enumerate() {
+      let j = solved.edges.chunks(2).position(|s| {
+        let s = (centre(s[0]), centre(s[1]));
+        (e[0], e[1]) == s || (e[1], e[0]) == s
+      });
+      match j {
+        None => errors.invalid_edges.push(EdgePos::natural_order()[2 * i]),
+        Some(j) if edges_seen[j] => {
+          errors.duplicate_edges.push(EdgePos::natural_order()[2 * i])
+        }
+        Some(j) => edges_seen[j] = true,
+      }
+    }
+    for (j, s) in solved.edges.chunks(2).enumerate() {
+      if !edges_seen[j] {
+        errors.missing_edges.push((centre(s[0]), centre(s[1])));
+      }
+    }
+
+    if errors != PieceErrors::default() {
+      return Err(CubeError::Pieces(Box::new(errors)));
+    }
+
+    // Every piece is present exactly once, so this can't fail.
+    let cc = CubieCube::from_cube(self).unwrap();
+
+    let twist = cc.co.iter().sum::<u8>() % 3;
+    if twist != 0 {
+      return Err(CubeError::TwistedCorners(twist));
+    }
+
+    if cc.eo.iter().sum::<u8>() % 2 != 0 {
+      return Err(CubeError::FlippedEdges);
+    }
+
+    if parity(&cc.cp) != parity(&cc.ep) {
+      return Err(CubeError::Parity);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parse_moves;
+  use crate::sticker_cube::CentrePos;
+
+  fn scrambled() -> Cube {
+    let mut c = Cube::solved();
+    c.do_moves(
+      &parse_moves("D2 F2 U' B2 R2 U F2 L2 U' R' B' L F' D L' U' F U2 R B x y")
+        .unwrap(),
+    );
+    c
+  }
+
+  #[test]
+  fn valid() {
+    assert_eq!(Ok(()), Cube::solved().validate());
+    assert_eq!(Ok(()), scrambled().validate());
+
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("M E S r u' f2 z").unwrap());
+    assert_eq!(Ok(()), c.validate());
+  }
+
+  #[test]
+  fn centres() {
+    let mut c = Cube::solved();
+    c.centres.swap(CentrePos::U as usize, CentrePos::D as usize);
+    assert_eq!(Err(CubeError::Centres), c.validate());
+
+    // A mirrored cube.
+    let mut c = Cube::solved();
+    c.centres.swap(CentrePos::R as usize, CentrePos::L as usize);
+    assert_eq!(Err(CubeError::Centres), c.validate());
+  }
+
+  #[test]
+  fn pieces() {
+    match Cube::invalid().validate() {
+      Err(CubeError::Centres) => (),
+      e => panic!("{:?}", e),
+    }
+
+    // Replace the UR edge with a second UF edge.
+    let mut c = Cube::solved();
+    c.edges[EdgePos::UR as usize] = Face::U;
+    c.edges[EdgePos::RU as usize] = Face::F;
+    assert_eq!(
+      Err(CubeError::Pieces(Box::new(PieceErrors {
+        duplicate_edges: vec![EdgePos::UR],
+        missing_edges: vec![(Face::U, Face::R)],
+        ..Default::default()
+      }))),
+      c.validate()
+    );
+
+    // A corner with two R stickers and an edge with two D stickers.
+    let mut c = Cube::solved();
+    c.corners[CornerPos::URF as usize] = Face::R;
+    c.edges[EdgePos::BD as usize] = Face::D;
+    assert_eq!(
+      Err(CubeError::Pieces(Box::new(PieceErrors {
+        invalid_corners: vec![CornerPos::URF],
+        missing_corners: vec![(Face::U, Face::R, Face::F)],
+        invalid_edges: vec![EdgePos::DB],
+        missing_edges: vec![(Face::D, Face::B)],
+        ..Default::default()
+      }))),
+      c.validate()
+    );
+
+    // Missing pieces are named by the stickers they should have, relative to
+    // the centres.
+    let mut c = Cube::solved();
+    c.do_move(crate::Move::Rotation(crate::Rotation::Y, 1));
+    c.corners[CornerPos::RFU as usize] = Face::U;
+    assert_eq!(
+      Err(CubeError::Pieces(Box::new(PieceErrors {
+        invalid_corners: vec![CornerPos::URF],
+        missing_corners: vec![(Face::U, Face::B, Face::R)],
+        ..Default::default()
+      }))),
+      c.validate()
+    );
+  }
+
+  #[test]
+  fn orientation_and_parity() {
+    // Twist URF clockwise.
+    let mut c = scrambled();
+    let pos = c.find_corner(Face::U, Face::R, Face::F) as usize / 3 * 3;
+    c.corners[pos..pos + 3].rotate_right(1);
+    assert_eq!(Err(CubeError::TwistedCorners(1)), c.validate());
+    c.corners[pos..pos + 3].rotate_right(1);
+    assert_eq!(Err(CubeError::TwistedCorners(2)), c.validate());
+
+    // Flip UF.
+    let mut c = scrambled();
+    let pos = c.find_edge(Face::U, Face::F) as usize & !1;
+    c.edges.swap(pos, pos + 1);
+    assert_eq!(Err(CubeError::FlippedEdges), c.validate());
+
+    // Swap UF and UB.
+    let mut c = Cube::solved();
+    c.edges[EdgePos::FU as usize] = Face::B;
+    c.edges[EdgePos::BU as usize] = Face::F;
+    assert_eq!(Err(CubeError::Parity), c.validate());
+  }
+}
@@ -1,110 +1,125 @@
 use crate::first_block::FBInfo;
-use cube::{Cube, Face, Move::*, Slice};
+use cube::{Cube, Face};
+use solver::heuristic::Combine;
 use solver::iddfs::IDDFSInfo;
-use solver::index::generic_corner_index;
-use solver::index::generic_corner_index_decode;
-use solver::index::Index;
-
-struct CMLL;
-
-impl Index for CMLL {
-  const NUM_ELEMS: u32 = 24 * 21 * 18 * 15;
-
-  fn from_cube(&self, c: &Cube) -> u32 {
-    generic_corner_index(
-      &c,
-      &[
-        (Face::U, Face::R, Face::F),
-        (Face::U, Face::F, Face::L),
-        (Face::U, Face::L, Face::B),
-        (Face::U, Face::B, Face::R),
-      ],
-    )
-  }
-
-  fn from_index(&self, i: u32) -> Cube {
-    let mut c = Cube::invalid();
-    generic_corner_index_decode(
-      &mut c,
-      i,
-      &[
-        (Face::U, Face::B, Face::R),
-        (Face::U, Face::L, Face::B),
-        (Face::U, Face::F, Face::L),
-        (Face::U, Face::R, Face::F),
-      ],
-    );
-    c
-  }
-}
-
-pub struct CMLLInfo(FBInfo, Box<[[u32; 7]]>, Box<[u8]>);
+use solver::index::{Index, PieceSetIndex};
+use solver::move_set::MoveSet;
+use solver::table_file::{
+  fingerprint, load_prune_table, load_transition_table, save_prune_table,
+  save_transition_table, TableError,
+};
+use solver::transition::TransitionTable;
+use std::io::{self, Read, Write};
+
+/// The U layer corners.
+const CMLL: PieceSetIndex<0, 4> = PieceSetIndex::new(
+  [],
+  [
+    (Face::U, Face::R, Face::F),
+    (Face::U, Face::F, Face::L),
+    (Face::U, Face::L, Face::B),
+    (Face::U, Face::B, Face::R),
+  ],
+);
+
+pub struct CMLLInfo(FBInfo, TransitionTable, Box<[u8]>);
 
 impl CMLLInfo {
+  /// Create the tables for searching with `<U, D, F, B, R, L, M>`.
   pub fn new() -> Self {
+    CMLLInfo::with_move_set(&"U D F B R L M".parse().unwrap())
+  }
+
+  /// Create the tables for searching with the generators of `move_set`.
+  pub fn with_move_set(move_set: &MoveSet) -> Self {
     use solver::pruning::*;
     use solver::transition::*;
     let c = Cube::solved();
-    let table = gen_transition_table(&CMLL);
-    let ptable = gen_prune_table(&table, 7, CMLL.from_cube(&c));
-    CMLLInfo(FBInfo::new(), table, ptable)
+    let fb = FBInfo::with_move_set(move_set);
+    let table = gen_transition_table(&CMLL, fb.move_set());
+    let ptable = gen_prune_table(&table, CMLL.from_cube(&c));
+    CMLLInfo(fb, table, ptable)
   }
 }
 
-fn sb_edge_index(c: &Cube) -> u32 {
-  use solver::index::generic_edge_index;
-  generic_edge_index(
-    &c,
-    &[(Face::D, Face::R), (Face::F, Face::R), (Face::B, Face::R)],
-  )
+impl CMLLInfo {
+  /// Write the tables to `w`, so they can be read by `CMLLInfo::load`.
+  pub fn save<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    let fp = fingerprint(&CMLL, self.move_set());
+    self.0.save(w)?;
+    save_transition_table(w, &self.1, fp)?;
+    save_prune_table(w, &self.2, fp)
+  }
+
+  /// Read tables written by `CMLLInfo::save` for the generators of
+  /// `move_set`.
+  pub fn load<R: Read>(
+    r: &mut R,
+    move_set: &MoveSet,
+  ) -> Result<Self, TableError> {
+    let fb = FBInfo::load(r, move_set)?;
+    let fp = fingerprint(&CMLL, fb.move_set());
+    let len = PieceSetIndex::<0, 4>::NUM_ELEMS as usize;
+    let table = load_transition_table(r, len, fp)?;
+    let init = CMLL.from_cube(&Cube::solved());
+    let ptable = load_prune_table(r, len, init, fp)?;
+    Ok(CMLLInfo(fb, table, ptable))
+  }
 }
 
-fn sb_corner_index(c: &Cube) -> u32 {
-  generic_corner_index(
-    &c,
-    &[(Face::D, Face::F, Face::R), (Face::D, Face::R, Face::B)],
-  )
+impl Default for CMLLInfo {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
+/// Edges of the second block (DR, FR, BR).
+const SB_EDGES: PieceSetIndex<3, 0> = PieceSetIndex::new(
+  [(Face::D, Face::R), (Face::F, Face::R), (Face::B, Face::R)],
+  [],
+);
+
+/// Corners of the second block (DFR, DRB).
+const SB_CORNERS: PieceSetIndex<0, 2> = PieceSetIndex::new(
+  [],
+  [(Face::D, Face::F, Face::R), (Face::D, Face::R, Face::B)],
+);
+
 fn sb_solved(c: &Cube) -> bool {
-  sb_edge_index(c) == sb_edge_index(&Cube::solved())
-    && sb_corner_index(c) == sb_corner_index(&Cube::solved())
+  let solved = Cube::solved();
+  SB_EDGES.from_cube(c) == SB_EDGES.from_cube(&solved)
+    && SB_CORNERS.from_cube(c) == SB_CORNERS.from_cube(&solved)
 }
 
 impl IDDFSInfo for CMLLInfo {
   type State = (Cube, u32, <FBInfo as IDDFSInfo>::State);
 
+  fn move_set(&self) -> &MoveSet {
+    self.1.move_set()
+  }
+
   fn is_solved(&self, (c, cmll, fb): &Self::State) -> bool {
     self.0.is_solved(fb)
       && *cmll == CMLL.from_cube(&Cube::solved())
-      && sb_solved(&c)
+      && sb_solved(c)
   }
 
   fn transition(&self, (c, cmll, fb): &Self::State, m: usize) -> Self::State {
     let mut c = *c;
-    // note: this matches the order in solver/src/iddfs.rs
-    let moves = [
-      Face(Face::U, 1),
-      Face(Face::D, 1),
-      Face(Face::F, 1),
-      Face(Face::B, 1),
-      Face(Face::R, 1),
-      Face(Face::L, 1),
-      Slice(Slice::M, 1),
-    ];
-    c.do_move(moves[m]);
+    c.do_move(self.move_set().moves()[m]);
     (c, self.1[*cmll as usize][m], self.0.transition(fb, m))
   }
 
   fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
-    self.0.prune(&state.2, depth_remaining)
-      || depth_remaining < self.2[state.1 as usize] as usize
+    let estimate = Combine::Max
+      .combine([self.0.estimate(&state.2), self.2[state.1 as usize]]);
+    depth_remaining < estimate as usize
   }
 }
 
 impl CMLLInfo {
   pub fn get_state(&self, c: &Cube) -> <Self as IDDFSInfo>::State {
-    (*c, CMLL.from_cube(&c), self.0.get_state(&c))
+    (*c, CMLL.from_cube(c), self.0.get_state(c))
   }
 }
 
@@ -112,7 +127,7 @@ impl CMLLInfo {
 mod tests {
   use super::*;
   use cube::parse_moves;
-  use solver::iddfs::iddfs;
+  use solver::iddfs::{all_solutions, iddfs};
   use solver::index::exhaustive_index_check;
 
   #[test]
@@ -120,6 +135,33 @@ mod tests {
     exhaustive_index_check(&CMLL);
   }
 
+  #[test]
+  fn bfs_prune_table() {
+    use solver::pruning::gen_prune_table_dfs;
+    let info = CMLLInfo::new();
+    let init = CMLL.from_cube(&Cube::solved());
+    assert_eq!(info.2, gen_prune_table_dfs(&info.1, 7, init));
+  }
+
+  #[test]
+  fn save_load() {
+    let move_set = "U D F B R L".parse().unwrap();
+    let info = CMLLInfo::with_move_set(&move_set);
+    let mut buf = Vec::new();
+    info.save(&mut buf).unwrap();
+    let loaded = CMLLInfo::load(&mut &buf[..], &move_set).unwrap();
+    assert_eq!(info.2, loaded.2);
+    assert_eq!(&move_set, loaded.move_set());
+
+    let other = "U D F B R L M".parse().unwrap();
+    assert!(matches!(
+      CMLLInfo::load(&mut &buf[..], &other),
+      Err(TableError::Fingerprint)
+    ));
+    buf.pop();
+    assert!(CMLLInfo::load(&mut &buf[..], &move_set).is_err());
+  }
+
   #[test]
   fn basic() {
     let c = Cube::solved();
@@ -135,4 +177,51 @@ mod tests {
     assert!(solved);
     assert_eq!(parse_moves("F' U2 F U F' U F").unwrap(), solution);
   }
+
+  #[test]
+  fn every_cmll_solution() {
+    let info = CMLLInfo::new();
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R U2 R' U' R U' R'").unwrap());
+
+    let solutions = all_solutions(info.get_state(&c), &info, 8, 0, 100);
+    assert!(solutions.len() > 1);
+    assert!(solutions.iter().all(|s| s.len() == 7));
+    assert!(solutions.contains(&parse_moves("F' U2 F U F' U F").unwrap()));
+    assert!(solutions.contains(&parse_moves("R U R' U R U2 R'").unwrap()));
+  }
+
+  #[test]
+  fn masks() {
+    use cube::Mask;
+
+    // The masks drawn for the first block and CMLL agree with when the
+    // steps are solved. Moves that turn the centres are left out, since
+    // the tables read the pieces relative to the centres.
+    let info = CMLLInfo::new();
+    let moves = parse_moves("U U2 U' R R2 R' F L2 D' B").unwrap();
+    let mut scrambles: Vec<Vec<_>> = ["R U R' U R U2 R'", "R2 U R U R' U'"]
+      .iter()
+      .map(|s| parse_moves(s).unwrap())
+      .collect();
+    for &a in &moves {
+      for &b in &moves {
+        scrambles.push(vec![a, b]);
+      }
+    }
+
+    let (mut fb_solved, mut cmll_solved) = (0, 0);
+    for scramble in &scrambles {
+      let mut c = Cube::solved();
+      c.do_moves(scramble);
+      let fb = Mask::first_block().is_solved(&c);
+      assert_eq!(fb, info.0.is_solved(&info.0.get_state(&c)));
+      let cmll = Mask::f2b_cmll().is_solved(&c);
+      assert_eq!(cmll, info.is_solved(&info.get_state(&c)));
+      fb_solved += fb as usize;
+      cmll_solved += cmll as usize;
+    }
+    assert!(0 < cmll_solved && cmll_solved < fb_solved);
+    assert!(fb_solved < scrambles.len());
+  }
 }
@@ -1,97 +1,46 @@
 use cube::{Cube, Face};
+use solver::heuristic::Combine;
 use solver::iddfs::IDDFSInfo;
-use solver::index::{
-  generic_corner_index, generic_corner_index_decode, generic_edge_index,
-  generic_edge_index_decode, Index,
-};
+use solver::index::{Index, PieceSetIndex};
+use solver::move_set::MoveSet;
 use solver::pruning::gen_prune_table;
-use solver::transition::gen_transition_table;
+use solver::table_file::{
+  fingerprint, load_prune_table, load_transition_table, save_prune_table,
+  save_transition_table, TableError,
+};
+use solver::transition::{gen_transition_table, TransitionTable};
+use std::io::{self, Read, Write};
 
 /// Edges of the first block (DL, FL, BL).
-pub struct FBEdges(Face, Face, Face);
-
-impl Default for FBEdges {
-  fn default() -> Self {
-    FBEdges(Face::D, Face::L, Face::F)
-  }
-}
-
-impl Index for FBEdges {
-  const NUM_ELEMS: u32 = 24 * 22 * 20;
-
-  fn from_cube(&self, c: &Cube) -> u32 {
-    generic_edge_index(
-      &c,
-      &[
-        (self.0, self.1),
-        (self.2, self.1),
-        (self.2.opposite(), self.1),
-      ],
-    )
-  }
-
-  fn from_index(&self, i: u32) -> Cube {
-    let mut c = Cube::invalid();
-    generic_edge_index_decode(
-      &mut c,
-      i,
-      &[
-        (self.2.opposite(), self.1),
-        (self.2, self.1),
-        (self.0, self.1),
-      ],
-    );
-    c
-  }
-}
+pub type FBEdges = PieceSetIndex<3, 0>;
 
 /// Corners of the first block (DLF, DBL).
-pub struct FBCorners(Face, Face, Face);
+pub type FBCorners = PieceSetIndex<0, 2>;
 
-impl Default for FBCorners {
-  fn default() -> Self {
-    FBCorners(Face::D, Face::L, Face::F)
-  }
+/// Get the first block edges of a cube whose D, L and F centres are `d`, `l`
+/// and `f`.
+pub fn fb_edges(d: Face, l: Face, f: Face) -> FBEdges {
+  PieceSetIndex::new([(d, l), (f, l), (f.opposite(), l)], [])
 }
 
-impl Index for FBCorners {
-  const NUM_ELEMS: u32 = 24 * 21;
-
-  fn from_cube(&self, c: &Cube) -> u32 {
-    generic_corner_index(
-      &c,
-      &[
-        (self.0, self.1, self.2),
-        (self.0, self.2.opposite(), self.1),
-      ],
-    )
-  }
-
-  fn from_index(&self, i: u32) -> Cube {
-    let mut c = Cube::invalid();
-    generic_corner_index_decode(
-      &mut c,
-      i,
-      &[
-        (self.0, self.2.opposite(), self.1),
-        (self.0, self.1, self.2),
-      ],
-    );
-    c
-  }
+/// Get the first block corners of a cube whose D, L and F centres are `d`,
+/// `l` and `f`.
+pub fn fb_corners(d: Face, l: Face, f: Face) -> FBCorners {
+  PieceSetIndex::new([], [(d, l, f), (d, f.opposite(), l)])
 }
 
 /// IDDFS Info for the first block.
-pub struct FBInfo(Box<[[u32; 7]]>, Box<[[u32; 7]]>, Box<[u8]>, Box<[u8]>);
+pub struct FBInfo(TransitionTable, TransitionTable, Box<[u8]>, Box<[u8]>);
 
 impl IDDFSInfo for FBInfo {
   type State = (u32, u32);
 
+  fn move_set(&self) -> &MoveSet {
+    self.0.move_set()
+  }
+
   fn is_solved(&self, state: &Self::State) -> bool {
-    let c = Cube::solved();
-    let fbe = FBEdges::default();
-    let fbc = FBCorners::default();
-    *state == (fbe.from_cube(&c), fbc.from_cube(&c))
+    *state == self.get_state(&Cube::solved())
   }
 
   fn transition(&self, state: &Self::State, m: usize) -> Self::State {
@@ -99,42 +48,86 @@ impl IDDFSInfo for FBInfo {
   }
 
   fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
-    depth_remaining
-      < std::cmp::max(self.2[state.0 as usize], self.3[state.1 as usize])
-        as usize
+    depth_remaining < self.estimate(state) as usize
   }
 }
 
 impl FBInfo {
+  /// Create the tables for searching with `<U, D, F, B, R, L, M>`.
   pub fn new() -> Self {
+    FBInfo::with_move_set(&"U D F B R L M".parse().unwrap())
+  }
+
+  /// Create the tables for searching with the generators of `move_set`.
+  pub fn with_move_set(move_set: &MoveSet) -> Self {
     let c = Cube::solved();
-    let fbe = FBEdges::default();
-    let e_table = gen_transition_table(&fbe);
-    let e_ptable = gen_prune_table(&e_table, 7, fbe.from_cube(&c));
-    let fbc = FBCorners::default();
-    let c_table = gen_transition_table(&fbc);
-    let c_ptable = gen_prune_table(&c_table, 4, fbc.from_cube(&c));
+    let fbe = fb_edges(Face::D, Face::L, Face::F);
+    let e_table = gen_transition_table(&fbe, move_set);
+    let e_ptable = gen_prune_table(&e_table, fbe.from_cube(&c));
+    let fbc = fb_corners(Face::D, Face::L, Face::F);
+    let c_table = gen_transition_table(&fbc, move_set);
+    let c_ptable = gen_prune_table(&c_table, fbc.from_cube(&c));
     FBInfo(e_table, c_table, e_ptable, c_ptable)
   }
 
+  /// Get a lower bound on the moves needed to solve `state`.
+  pub fn estimate(&self, state: &<Self as IDDFSInfo>::State) -> u8 {
+    Combine::Max.combine([self.2[state.0 as usize], self.3[state.1 as usize]])
+  }
+
+  /// Write the tables to `w`, so they can be read by `FBInfo::load`.
+  pub fn save<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    let (e_fp, c_fp) = FBInfo::fingerprints(self.move_set());
+    save_transition_table(w, &self.0, e_fp)?;
+    save_transition_table(w, &self.1, c_fp)?;
+    save_prune_table(w, &self.2, e_fp)?;
+    save_prune_table(w, &self.3, c_fp)
+  }
+
+  /// Read tables written by `FBInfo::save` for the generators of
+  /// `move_set`. Tables for other move sets or older versions of the indexes
+  /// are rejected.
+  pub fn load<R: Read>(
+    r: &mut R,
+    move_set: &MoveSet,
+  ) -> Result<Self, TableError> {
+    let (e_fp, c_fp) = FBInfo::fingerprints(move_set);
+    let (e_len, c_len) =
+      (FBEdges::NUM_ELEMS as usize, FBCorners::NUM_ELEMS as usize);
+    let e_table = load_transition_table(r, e_len, e_fp)?;
+    let c_table = load_transition_table(r, c_len, c_fp)?;
+    let c = Cube::solved();
+    let e_init = fb_edges(Face::D, Face::L, Face::F).from_cube(&c);
+    let c_init = fb_corners(Face::D, Face::L, Face::F).from_cube(&c);
+    let e_ptable = load_prune_table(r, e_len, e_init, e_fp)?;
+    let c_ptable = load_prune_table(r, c_len, c_init, c_fp)?;
+    Ok(FBInfo(e_table, c_table, e_ptable, c_ptable))
+  }
+
+  fn fingerprints(move_set: &MoveSet) -> (u64, u64) {
+    (
+      fingerprint(&fb_edges(Face::D, Face::L, Face::F), move_set),
+      fingerprint(&fb_corners(Face::D, Face::L, Face::F), move_set),
+    )
+  }
+
   pub fn get_indexes(&self, c: &Cube) -> (FBEdges, FBCorners) {
     use cube::sticker_cube::CentrePos;
-    let fbe = FBEdges(
-      c.centres[CentrePos::D as usize],
-      c.centres[CentrePos::L as usize],
-      c.centres[CentrePos::F as usize],
-    );
-    let fbc = FBCorners(
-      c.centres[CentrePos::D as usize],
-      c.centres[CentrePos::L as usize],
-      c.centres[CentrePos::F as usize],
-    );
-    (fbe, fbc)
+    let d = c.centres[CentrePos::D as usize];
+    let l = c.centres[CentrePos::L as usize];
+    let f = c.centres[CentrePos::F as usize];
+    (fb_edges(d, l, f), fb_corners(d, l, f))
   }
 
   pub fn get_state(&self, c: &Cube) -> <Self as IDDFSInfo>::State {
-    let (fbe, fbc) = self.get_indexes(&c);
-    (fbe.from_cube(&c), fbc.from_cube(&c))
+    let (fbe, fbc) = self.get_indexes(c);
+    (fbe.from_cube(c), fbc.from_cube(c))
+  }
+}
+
+impl Default for FBInfo {
+  fn default() -> Self {
+    Self::new()
   }
 }
 
@@ -147,12 +140,104 @@ mod tests {
 
   #[test]
   fn exhaustive_fbe() {
-    exhaustive_index_check(&FBEdges::default());
+    exhaustive_index_check(&fb_edges(Face::D, Face::L, Face::F));
   }
 
   #[test]
   fn exhaustive_fbc() {
-    exhaustive_index_check(&FBCorners::default());
+    exhaustive_index_check(&fb_corners(Face::D, Face::L, Face::F));
+  }
+
+  #[test]
+  fn bfs_prune_tables() {
+    use solver::pruning::gen_prune_table_dfs;
+    let info = FBInfo::new();
+    let c = Cube::solved();
+    let (fbe, fbc) = info.get_indexes(&c);
+    assert_eq!(info.2, gen_prune_table_dfs(&info.0, 7, fbe.from_cube(&c)));
+    assert_eq!(info.3, gen_prune_table_dfs(&info.1, 4, fbc.from_cube(&c)));
+  }
+
+  #[test]
+  fn compact_prune_tables() {
+    use solver::pruning::{Mod3Table, NibbleTable, PruneTable};
+    let info = FBInfo::new();
+    let init = info.get_state(&Cube::solved()).0;
+    let nibble = NibbleTable::generate(&info.0, init);
+    let mod3 = Mod3Table::generate(&info.0, init);
+    for i in 0..info.0.len() as u32 {
+      let d = info.2[i as usize];
+      assert_eq!(d, nibble.distance(&info.0, i));
+      assert_eq!(d, mod3.distance(&info.0, i));
+      for &next in &info.0[i as usize] {
+        assert_eq!(info.2[next as usize], mod3.next_distance(d, next));
+      }
+    }
+  }
+
+  #[test]
+  fn save_load() {
+    let move_set = "U D F B R L M".parse().unwrap();
+    let info = FBInfo::with_move_set(&move_set);
+    let mut buf = Vec::new();
+    info.save(&mut buf).unwrap();
+
+    let loaded = FBInfo::load(&mut &buf[..], &move_set).unwrap();
+    assert_eq!(info.2, loaded.2);
+    assert_eq!(info.3, loaded.3);
+    assert!((0..info.0.len()).all(|i| info.0[i] == loaded.0[i]));
+
+    let other = "U D F B R L".parse().unwrap();
+    assert!(matches!(
+      FBInfo::load(&mut &buf[..], &other),
+      Err(TableError::Fingerprint)
+    ));
+  }
+
+  #[test]
+  fn combined_info() {
+    use cube::parse_moves;
+    use solver::heuristic::{CombinedInfo, PatternDatabase};
+    use solver::solve::{SolveOptions, Solver};
+
+    let info = FBInfo::new();
+    let move_set = info.move_set();
+    let combined = CombinedInfo::<2>::new(
+      [
+        PatternDatabase::new(fb_edges(Face::D, Face::L, Face::F), move_set),
+        PatternDatabase::new(fb_corners(Face::D, Face::L, Face::F), move_set),
+      ],
+      Combine::Max,
+    );
+
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R U' F2 L D' B R2 U").unwrap());
+    let state = info.get_state(&c);
+    assert_eq!(
+      info.estimate(&state),
+      combined.estimate(&combined.get_state(&c))
+    );
+    let solution = Solver::new(&info, SolveOptions::default()).solve(state);
+    let combined_solution = Solver::new(&combined, SolveOptions::default())
+      .solve(combined.get_state(&c));
+    assert_eq!(solution.solution, combined_solution.solution);
+  }
+
+  #[test]
+  fn parallel() {
+    use cube::parse_moves;
+    use solver::parallel::parallel_solve;
+    use solver::solve::{SolveOptions, Solver};
+
+    let info = FBInfo::new();
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R U' F2 L D' B R2 U F' L2").unwrap());
+    let state = info.get_state(&c);
+    let options = SolveOptions::default();
+    let expected = Solver::new(&info, options.clone()).solve(state);
+    let result = parallel_solve(&info, state, &options, 4);
+    assert!(expected.solution.is_some());
+    assert_eq!(expected.solution, result.solution);
   }
 
   #[test]
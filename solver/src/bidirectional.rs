@@ -0,0 +1,228 @@
+//! Meet in the middle search for every optimal sequence between two states.
+//!
+//! Breadth first searches from the start and from the goal each only need to
+//! reach half way, which is far fewer states than a search from one side.
+//! Neither search needs a transition or pruning table, so this works with
+//! any `Index`, although an index with fewer elements is faster.
+
+use crate::index::Index;
+use crate::move_set::MoveSet;
+use cube::{Cube, Move};
+use std::collections::HashMap;
+
+/// Get the moves made by each power of each generator of `move_set`, and the
+/// index each one reaches from index `i`.
+fn neighbours<I: Index>(
+  index: &I,
+  move_set: &MoveSet,
+  i: u32,
+) -> Vec<(Move, u32)> {
+  let c = index.from_index(i);
+  let mut next = Vec::new();
+  for g in 0..move_set.len() {
+    for n in 1..=move_set.powers(g) {
+      let m = move_set.power(g, n);
+      let mut c = c;
+      c.do_move(m);
+      next.push((m, index.from_cube(&c)));
+    }
+  }
+  next
+}
+
+/// The states reached by a breadth first search, and their distances.
+struct Frontier {
+  dists: HashMap<u32, u8>,
+  layer: Vec<u32>,
+  depth: u8,
+}
+
+impl Frontier {
+  fn new(i: u32) -> Self {
+    let mut dists = HashMap::new();
+    dists.insert(i, 0);
+    Frontier {
+      dists,
+      layer: vec![i],
+      depth: 0,
+    }
+  }
+
+  /// Search one move deeper.
+  fn expand<I: Index>(&mut self, index: &I, move_set: &MoveSet) {
+    self.depth += 1;
+    let mut layer = Vec::new();
+    for &i in &self.layer {
+      for (_, next) in neighbours(index, move_set, i) {
+        if !self.dists.contains_key(&next) {
+          self.dists.insert(next, self.depth);
+          layer.push(next);
+        }
+      }
+    }
+    self.layer = layer;
+  }
+
+  /// Get the length of the shortest path through the last layer to a state
+  /// reached by `other`.
+  fn meet(&self, other: &Frontier) -> Option<usize> {
+    self
+      .layer
+      .iter()
+      .filter_map(|i| other.dists.get(i))
+      .map(|&d| (self.depth + d) as usize)
+      .min()
+  }
+}
+
+/// Find every optimal sequence of moves from `start` to `goal`, as far as the
+/// pieces of `index` can tell, using the generators of `move_set`. At most
+/// `limit` sequences are returned, and none are if the sequences are longer
+/// than `max_depth` moves.
+pub fn optimal_sequences<I: Index>(
+  index: &I,
+  move_set: &MoveSet,
+  start: &Cube,
+  goal: &Cube,
+  max_depth: usize,
+  limit: usize,
+) -> Vec<Vec<Move>> {
+  let start = index.from_cube(start);
+  let goal = index.from_cube(goal);
+  if start == goal {
+    return vec![vec![]];
+  }
+
+  let mut forward = Frontier::new(start);
+  let mut backward = Frontier::new(goal);
+  let depth = loop {
+    if (forward.depth + backward.depth) as usize >= max_depth
+      || forward.layer.is_empty() && backward.layer.is_empty()
+    {
+      return vec![];
+    }
+    // Grow the side with the smaller frontier.
+    let (grow, other) = if forward.layer.len() <= backward.layer.len()
+      && !forward.layer.is_empty()
+      || backward.layer.is_empty()
+    {
+      (&mut forward, &backward)
+    } else {
+      (&mut backward, &forward)
+    };
+    grow.expand(index, move_set);
+    if let Some(depth) = grow.meet(other) {
+      break depth;
+    }
+  };
+
+  let mut search = Paths {
+    index,
+    move_set,
+    forward: &forward,
+    backward: &backward,
+    depth,
+    limit,
+    path: Vec::with_capacity(depth),
+    paths: Vec::new(),
+  };
+  search.find(start);
+  search.paths
+}
+
+/// Enumerates the paths that are optimal according to the two frontiers.
+struct Paths<'a, I> {
+  index: &'a I,
+  move_set: &'a MoveSet,
+  forward: &'a Frontier,
+  backward: &'a Frontier,
+  depth: usize,
+  limit: usize,
+  path: Vec<Move>,
+  paths: Vec<Vec<Move>>,
+}
+
+impl<I: Index> Paths<'_, I> {
+  /// Test if `i` can be the state after `step` moves of an optimal path.
+  fn on_path(&self, i: u32, step: usize) -> bool {
+    let dist = |f: &Frontier, d: usize| {
+      d > f.depth as usize || f.dists.get(&i) == Some(&(d as u8))
+    };
+    dist(self.forward, step) && dist(self.backward, self.depth - step)
+  }
+
+  fn find(&mut self, i: u32) {
+    let step = self.path.len();
+    if step == self.depth {
+      self.paths.push(self.path.clone());
+      return;
+    }
+    for (m, next) in neighbours(self.index, self.move_set, i) {
+      if self.paths.len() >= self.limit {
+        return;
+      }
+      if self.on_path(next, step + 1) {
+        self.path.push(m);
+        self.find(next);
+        self.path.pop();
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::index::example::{UF, UL, UL_CORNERS};
+  use cube::parse_moves;
+
+  #[test]
+  fn single_edge() {
+    let move_set = "U D F B R L M".parse().unwrap();
+    let solved = Cube::solved();
+    let c = Cube::from_moves("U2 M").unwrap();
+    let sequences = optimal_sequences(&UF, &move_set, &c, &solved, 5, 100);
+    assert_eq!(4, sequences.len());
+    assert!(sequences.contains(&parse_moves("M' U2").unwrap()));
+    assert!(sequences.iter().all(|s| s.len() == 2));
+
+    let r = Cube::from_moves("R").unwrap();
+    assert_eq!(
+      vec![Vec::<Move>::new()],
+      optimal_sequences(&UL, &move_set, &r, &solved, 5, 100)
+    );
+    assert_eq!(
+      1,
+      optimal_sequences(&UF, &move_set, &c, &solved, 5, 1).len()
+    );
+    assert!(optimal_sequences(&UF, &move_set, &c, &solved, 1, 10).is_empty());
+  }
+
+  #[test]
+  fn between_states() {
+    let move_set = "R U".parse().unwrap();
+    let start = Cube::from_moves("R U R' U R U2 R'").unwrap();
+    let goal = Cube::from_moves("R U2 R' U' R U' R'").unwrap();
+    let sequences =
+      optimal_sequences(&UL_CORNERS, &move_set, &start, &goal, 20, 1000);
+    assert!(!sequences.is_empty());
+    let len = sequences[0].len();
+    for s in &sequences {
+      assert_eq!(len, s.len());
+      let mut c = start;
+      c.do_moves(s);
+      assert_eq!(UL_CORNERS.from_cube(&goal), UL_CORNERS.from_cube(&c));
+    }
+
+    // There is nothing shorter.
+    assert!(optimal_sequences(
+      &UL_CORNERS,
+      &move_set,
+      &start,
+      &goal,
+      len - 1,
+      1
+    )
+    .is_empty());
+  }
+}
@@ -0,0 +1,297 @@
+//! Pruning heuristics made of several pattern databases, each a pruning table
+//! for one `Index`.
+
+use crate::iddfs::IDDFSInfo;
+use crate::index::Index;
+use crate::move_set::MoveSet;
+use crate::pruning::PruneTable;
+use crate::transition::{gen_transition_table, TransitionTable};
+use cube::Cube;
+
+/// How to combine the estimates of several pattern databases.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Combine {
+  /// Take the largest estimate. This is admissible for any tables.
+  Max,
+  /// Add the estimates. This is only admissible if every move changes the
+  /// index of at most one of the tables, so each move is counted by one
+  /// table. A pruning table only counts the moves that change its index,
+  /// since the others leave it at the same distance.
+  Sum,
+}
+
+impl Combine {
+  /// Combine `estimates` into one estimate.
+  pub fn combine<T: IntoIterator<Item = u8>>(self, estimates: T) -> u8 {
+    let estimates = estimates.into_iter();
+    match self {
+      Combine::Max => estimates.max().unwrap_or(0),
+      Combine::Sum => estimates.fold(0, u8::saturating_add),
+    }
+  }
+}
+
+/// The transition and pruning tables for an `Index`. `P` is how the pruning
+/// table is stored.
+pub struct PatternDatabase<P: PruneTable = Box<[u8]>> {
+  from_cube: Box<dyn Fn(&Cube) -> u32 + Send + Sync>,
+  trans_table: TransitionTable,
+  prune_table: P,
+  solved: u32,
+}
+
+impl<P: PruneTable> PatternDatabase<P> {
+  /// Generate the tables for `index` and the generators of `move_set`.
+  pub fn new<I: Index + Send + Sync + 'static>(
+    index: I,
+    move_set: &MoveSet,
+  ) -> Self {
+    let solved = index.from_cube(&Cube::solved());
+    let trans_table = gen_transition_table(&index, move_set);
+    let prune_table = P::generate(&trans_table, solved);
+    PatternDatabase {
+      from_cube: Box::new(move |c| index.from_cube(c)),
+      trans_table,
+      prune_table,
+      solved,
+    }
+  }
+
+  /// Get the index of `c`.
+  pub fn index(&self, c: &Cube) -> u32 {
+    (self.from_cube)(c)
+  }
+
+  /// Get the move set the tables were generated for.
+  pub fn move_set(&self) -> &MoveSet {
+    self.trans_table.move_set()
+  }
+
+  /// Get the index reached by doing generator `m` from index `i`.
+  pub fn transition(&self, i: u32, m: usize) -> u32 {
+    self.trans_table[i as usize][m]
+  }
+
+  /// Get the fewest moves needed to solve index `i`.
+  pub fn distance(&self, i: u32) -> u8 {
+    self.prune_table.distance(&self.trans_table, i)
+  }
+
+  /// Get the fewest moves needed to solve index `i`, which is one move away
+  /// from an index that needs `prev` moves.
+  pub fn next_distance(&self, prev: u8, i: u32) -> u8 {
+    self.prune_table.next_distance(prev, i)
+  }
+
+  /// Test if index `i` is solved.
+  pub fn is_solved(&self, i: u32) -> bool {
+    i == self.solved
+  }
+
+  /// Test if generator `m` changes any index that can be reached from the
+  /// solved index.
+  pub fn is_changed_by(&self, m: usize) -> bool {
+    (0..self.trans_table.len()).any(|i| {
+      self.prune_table.is_reachable(i as u32)
+        && self.trans_table[i][m] != i as u32
+    })
+  }
+}
+
+/// An `IDDFSInfo` for a step made of the pieces of `N` pattern databases.
+/// The step is solved when every database is, and the search is pruned with
+/// the combined estimate of every database.
+///
+/// The state holds the index and the distance for each database, so the
+/// distances are updated with `PruneTable::next_distance` as the search
+/// goes.
+pub struct CombinedInfo<const N: usize, P: PruneTable = Box<[u8]>> {
+  tables: [PatternDatabase<P>; N],
+  combine: Combine,
+}
+
+impl<const N: usize, P: PruneTable> CombinedInfo<N, P> {
+  /// Panics if there are no tables, if the tables were generated for
+  /// different move sets, or if `combine` is `Combine::Sum` and a generator
+  /// changes the index of more than one table.
+  pub fn new(tables: [PatternDatabase<P>; N], combine: Combine) -> Self {
+    assert!(N > 0, "there must be at least one table");
+    assert!(tables.iter().all(|t| t.move_set() == tables[0].move_set()));
+    if combine == Combine::Sum {
+      for m in 0..tables[0].move_set().len() {
+        let changed = tables.iter().filter(|t| t.is_changed_by(m)).count();
+        assert!(changed <= 1, "tables can't be summed, a move changes both");
+      }
+    }
+    CombinedInfo { tables, combine }
+  }
+
+  /// Get the index of `c` in each table, and its distance.
+  pub fn get_state(&self, c: &Cube) -> ([u32; N], [u8; N]) {
+    let mut state = ([0; N], [0; N]);
+    for (i, t) in self.tables.iter().enumerate() {
+      state.0[i] = t.index(c);
+      state.1[i] = t.distance(state.0[i]);
+    }
+    state
+  }
+
+  /// Get the combined estimate of the moves needed to solve `state`.
+  pub fn estimate(&self, state: &([u32; N], [u8; N])) -> u8 {
+    self.combine.combine(state.1)
+  }
+}
+
+impl<const N: usize, P: PruneTable> IDDFSInfo for CombinedInfo<N, P> {
+  type State = ([u32; N], [u8; N]);
+
+  fn move_set(&self) -> &MoveSet {
+    self.tables[0].move_set()
+  }
+
+  fn is_solved(&self, state: &Self::State) -> bool {
+    self
+      .tables
+      .iter()
+      .zip(&state.0)
+      .all(|(t, &i)| t.is_solved(i))
+  }
+
+  fn transition(&self, state: &Self::State, m: usize) -> Self::State {
+    let mut next = *state;
+    for (i, t) in self.tables.iter().enumerate() {
+      next.0[i] = t.transition(state.0[i], m);
+      next.1[i] = t.next_distance(state.1[i], next.0[i]);
+    }
+    next
+  }
+
+  fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
+    depth_remaining < self.estimate(state) as usize
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::iddfs::all_solutions;
+  use crate::index::example::{UF, UL};
+  use crate::index::PieceSetIndex;
+  use crate::pruning::Mod3Table;
+  use crate::solve::{SolveOptions, Solver};
+  use cube::{parse_moves, Face};
+
+  #[test]
+  fn combine() {
+    assert_eq!(3, Combine::Max.combine(vec![1, 3, 2]));
+    assert_eq!(6, Combine::Sum.combine(vec![1, 3, 2]));
+    assert_eq!(u8::MAX, Combine::Sum.combine(vec![u8::MAX, 1]));
+    assert_eq!(0, Combine::Max.combine(vec![]));
+  }
+
+  #[test]
+  fn combined_info() {
+    let move_set = MoveSet::faces();
+    let info = CombinedInfo::<2>::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(UL, &move_set),
+      ],
+      Combine::Max,
+    );
+
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("F L'").unwrap());
+    let state = info.get_state(&c);
+    assert!(!info.is_solved(&state));
+    // Each edge is one move from solved.
+    assert_eq!(1, info.estimate(&state));
+
+    let solutions = all_solutions(state, &info, 4, 0, 10);
+    assert_eq!(2, solutions.len());
+    assert!(solutions.contains(&parse_moves("L F'").unwrap()));
+    assert!(solutions.contains(&parse_moves("F' L").unwrap()));
+  }
+
+  #[test]
+  fn compact_tables() {
+    let move_set = MoveSet::faces();
+    let info = CombinedInfo::<2>::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(UL, &move_set),
+      ],
+      Combine::Max,
+    );
+    let mod3 = CombinedInfo::<2, Mod3Table>::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(UL, &move_set),
+      ],
+      Combine::Max,
+    );
+
+    // The distances kept in the state match the full table after every
+    // move.
+    let mut c = Cube::solved();
+    let mut state = mod3.get_state(&c);
+    for m in parse_moves("R U' F2 L D B' R2 U").unwrap() {
+      let i = move_set.position(&m).unwrap();
+      for _ in 0..m.amount() {
+        state = mod3.transition(&state, i);
+      }
+      c.do_move(m);
+      assert_eq!(info.get_state(&c), state);
+      assert_eq!(state, mod3.get_state(&c));
+    }
+
+    let result = Solver::new(&mod3, SolveOptions::default()).solve(state);
+    let expected =
+      Solver::new(&info, SolveOptions::default()).solve(info.get_state(&c));
+    assert_eq!(expected.solution, result.solution);
+  }
+
+  #[test]
+  fn sum() {
+    // U and D never move the same pieces, so the distances can be added.
+    let df = PieceSetIndex::<1, 0>::new([(Face::D, Face::F)], []);
+    let move_set = "U D".parse().unwrap();
+    let info = CombinedInfo::<2>::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(df, &move_set),
+      ],
+      Combine::Sum,
+    );
+
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("U D2").unwrap());
+    let state = info.get_state(&c);
+    assert_eq!(2, info.estimate(&state));
+    let result = Solver::new(&info, SolveOptions::default()).solve(state);
+    let solution = result.solution.unwrap();
+    assert_eq!(2, solution.len());
+    c.do_moves(&solution);
+    assert!(info.is_solved(&info.get_state(&c)));
+  }
+
+  #[test]
+  #[should_panic]
+  fn sum_overlapping() {
+    // U moves both edges.
+    let move_set = MoveSet::faces();
+    CombinedInfo::<2>::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(UL, &move_set),
+      ],
+      Combine::Sum,
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "at least one table")]
+  fn no_tables() {
+    CombinedInfo::<0>::new([], Combine::Max);
+  }
+}
@@ -1,14 +1,16 @@
-use cube::{
-  Face,
-  Move::{self, *},
-  Slice,
-};
+use crate::move_set::MoveSet;
+use crate::stats::Stats;
+use cube::Move;
 
 /// A trait to customise the IDDFS algorithm.
 pub trait IDDFSInfo {
   /// Type of the state for the current search.
   type State: Copy;
 
+  /// The moves that the search uses. `transition` is called with the number
+  /// of a generator of this set.
+  fn move_set(&self) -> &MoveSet;
+
   /// Check if `state` is solved.
   fn is_solved(&self, state: &Self::State) -> bool;
 
@@ -26,36 +28,134 @@ pub fn iddfs<I: IDDFSInfo>(
   depth_remaining: usize,
   solution: &mut Vec<Move>,
 ) -> bool {
+  iddfs_with_stats(state, info, depth_remaining, solution, &mut ())
+}
+
+/// `iddfs`, recording statistics about the search in `stats`.
+pub fn iddfs_with_stats<I: IDDFSInfo, S: Stats>(
+  state: I::State,
+  info: &I,
+  depth_remaining: usize,
+  solution: &mut Vec<Move>,
+  stats: &mut S,
+) -> bool {
+  let depth = depth_remaining + solution.len();
+  stats.start_depth(depth);
+  let mut control = Each(|_: &[Move]| true, stats);
+  let found = search(state, info, depth_remaining, solution, &mut control);
+  control.1.end_depth(depth);
+  found
+}
+
+/// Search for every solution of exactly `depth` moves, calling `f` with each
+/// one. `f` returns `true` to stop the search, and the result is whether the
+/// search was stopped.
+pub fn iddfs_each<I: IDDFSInfo, F: FnMut(&[Move]) -> bool>(
+  state: I::State,
+  info: &I,
+  depth: usize,
+  f: F,
+) -> bool {
+  let mut solution = Vec::with_capacity(depth);
+  search(state, info, depth, &mut solution, &mut Each(f, &mut ()))
+}
+
+/// Find the optimal solutions, and optionally the solutions that are up to
+/// `extra_depth` moves longer, trying at most `max_depth` moves. Solutions are
+/// returned shortest first, and at most `limit` are returned.
+pub fn all_solutions<I: IDDFSInfo>(
+  state: I::State,
+  info: &I,
+  max_depth: usize,
+  extra_depth: usize,
+  limit: usize,
+) -> Vec<Vec<Move>> {
+  let mut solutions = Vec::new();
+  let mut optimal = None;
+  for depth in 0..=max_depth {
+    if optimal.is_some_and(|o| depth > o + extra_depth) {
+      break;
+    }
+    iddfs_each(state, info, depth, |s| {
+      solutions.push(s.to_vec());
+      solutions.len() >= limit
+    });
+    if !solutions.is_empty() {
+      optimal.get_or_insert(depth);
+    }
+    if solutions.len() >= limit {
+      break;
+    }
+  }
+  solutions
+}
+
+/// Decides when a search stops.
+pub(crate) trait Control {
+  /// Called for each state visited. Returns `true` to stop the search.
+  fn node(&mut self) -> bool {
+    false
+  }
+
+  /// Called after each call to `IDDFSInfo::prune`, with its result.
+  fn pruned(&mut self, _pruned: bool) {}
+
+  /// Called with each solution. Returns `true` to stop the search.
+  fn solved(&mut self, solution: &[Move]) -> bool;
+}
+
+/// Calls a closure with each solution, and records statistics.
+struct Each<'a, F, S>(F, &'a mut S);
+
+impl<F: FnMut(&[Move]) -> bool, S: Stats> Control for Each<'_, F, S> {
+  fn node(&mut self) -> bool {
+    self.1.node();
+    false
+  }
+
+  fn pruned(&mut self, pruned: bool) {
+    self.1.prune(pruned);
+  }
+
+  fn solved(&mut self, solution: &[Move]) -> bool {
+    (self.0)(solution)
+  }
+}
+
+/// Depth first search to exactly `depth_remaining` moves, until `control`
+/// stops it.
+pub(crate) fn search<I: IDDFSInfo, C: Control>(
+  state: I::State,
+  info: &I,
+  depth_remaining: usize,
+  solution: &mut Vec<Move>,
+  control: &mut C,
+) -> bool {
+  if control.node() {
+    return true;
+  }
   if depth_remaining == 0 {
-    return info.is_solved(&state);
-  }
-
-  for (i, &m) in [
-    Face(Face::U, 1),
-    Face(Face::D, 1),
-    Face(Face::F, 1),
-    Face(Face::B, 1),
-    Face(Face::R, 1),
-    Face(Face::L, 1),
-    Slice(Slice::M, 1),
-  ]
-  .iter()
-  .enumerate()
-  {
+    return info.is_solved(&state) && control.solved(solution);
+  }
+
+  let move_set = info.move_set();
+  for (i, &m) in move_set.moves().iter().enumerate() {
     if skip_face(m, solution) {
       continue;
     }
 
     let mut next = state;
-    for n in 1..4 {
+    for n in 1..=move_set.powers(i) {
       next = info.transition(&next, i);
 
-      if info.prune(&next, depth_remaining - 1) {
+      let pruned = info.prune(&next, depth_remaining - 1);
+      control.pruned(pruned);
+      if pruned {
         continue;
       }
 
-      solution.push(m.with_amount(n));
-      if iddfs::<I>(next, info, depth_remaining - 1, solution) {
+      solution.push(move_set.power(i, n));
+      if search(next, info, depth_remaining - 1, solution, control) {
         return true;
       }
       solution.pop();
@@ -95,32 +195,42 @@ mod tests {
 
   use crate::index::example::UF;
   use crate::pruning::gen_prune_table;
-  use crate::transition::gen_transition_table;
+  use crate::stats::DepthStats;
+  use crate::transition::{gen_transition_table, TransitionTable};
   use cube::sticker_cube::EdgePos;
+  use cube::{Face, Move::*, Slice};
 
-  #[test]
-  fn minimal_uf() {
-    struct UFInfo(Box<[[u32; 7]]>, Box<[u8]>);
+  struct UFInfo(TransitionTable, Box<[u8]>);
 
-    impl IDDFSInfo for UFInfo {
-      type State = u32;
+  impl IDDFSInfo for UFInfo {
+    type State = u32;
 
-      fn is_solved(&self, &s: &Self::State) -> bool {
-        s == 0
-      }
+    fn move_set(&self) -> &MoveSet {
+      self.0.move_set()
+    }
 
-      fn transition(&self, state: &Self::State, m: usize) -> Self::State {
-        self.0[*state as usize][m]
-      }
+    fn is_solved(&self, &s: &Self::State) -> bool {
+      s == 0
+    }
 
-      fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
-        (depth_remaining as u8) < self.1[*state as usize]
-      }
+    fn transition(&self, state: &Self::State, m: usize) -> Self::State {
+      self.0[*state as usize][m]
+    }
+
+    fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
+      (depth_remaining as u8) < self.1[*state as usize]
     }
+  }
+
+  fn uf_info(move_set: &str) -> UFInfo {
+    let table = gen_transition_table(&UF, &move_set.parse().unwrap());
+    let ptable = gen_prune_table(&table, 0);
+    UFInfo(table, ptable)
+  }
 
-    let table = gen_transition_table(&UF);
-    let ptable = gen_prune_table(&table, 2, 0);
-    let info = UFInfo(table, ptable);
+  #[test]
+  fn minimal_uf() {
+    let info = uf_info("U D F B R L M");
 
     // This can use an EdgePos here since this index only looks at a single edge.
     let solved = iddfs(EdgePos::UF as u32, &info, 0, &mut Vec::new());
@@ -132,10 +242,84 @@ mod tests {
     let mut solution = Vec::new();
     let solved = iddfs(EdgePos::FU as u32, &info, 2, &mut solution);
     assert!(solved);
-    assert!(match &solution[..] {
-      [Face(Face::U, 2), Slice(Slice::M, 1)] => true,
-      _ => false,
+    assert!(matches!(
+      &solution[..],
+      [Face(Face::U, 2), Slice(Slice::M, 1)]
+    ));
+  }
+
+  #[test]
+  fn move_sets() {
+    let mut solution = Vec::new();
+    let info = uf_info("R U");
+    assert!(iddfs(EdgePos::DR as u32, &info, 2, &mut solution));
+    assert_eq!(vec![Face(Face::R, 2), Face(Face::U, 1)], solution);
+    // <R, U> can't flip edges.
+    assert!(!iddfs(EdgePos::FU as u32, &info, 4, &mut Vec::new()));
+
+    let mut solution = Vec::new();
+    let info = uf_info("U2 M");
+    assert!(iddfs(EdgePos::DB as u32, &info, 1, &mut solution));
+    assert_eq!(vec![Slice(Slice::M, 2)], solution);
+    assert!(!iddfs(EdgePos::UL as u32, &info, 4, &mut Vec::new()));
+  }
+
+  #[test]
+  fn every_solution() {
+    let info = uf_info("U D F B R L M");
+
+    let mut solutions = Vec::new();
+    let stopped = iddfs_each(EdgePos::FU as u32, &info, 2, |s| {
+      solutions.push(s.to_vec());
+      false
     });
+    assert!(!stopped);
+    assert_eq!(4, solutions.len());
+    assert!(solutions.contains(&vec![Face(Face::U, 2), Slice(Slice::M, 1)]));
+    assert!(solutions.contains(&vec![Slice(Slice::M, 3), Face(Face::U, 2)]));
+
+    assert_eq!(
+      solutions,
+      all_solutions(EdgePos::FU as u32, &info, 5, 0, 10)
+    );
+    assert_eq!(2, all_solutions(EdgePos::FU as u32, &info, 5, 0, 2).len());
+
+    let solutions = all_solutions(EdgePos::FU as u32, &info, 5, 1, 100);
+    assert!(solutions.len() > 4);
+    assert!(solutions[..4].iter().all(|s| s.len() == 2));
+    assert!(solutions[4..].iter().all(|s| s.len() == 3));
+
+    assert!(all_solutions(EdgePos::FU as u32, &info, 1, 2, 10).is_empty());
+  }
+
+  #[test]
+  fn stats() {
+    let info = uf_info("U D F B R L M");
+    let mut stats = DepthStats::new();
+    for depth in 0..3 {
+      iddfs_with_stats(
+        EdgePos::FU as u32,
+        &info,
+        depth,
+        &mut Vec::new(),
+        &mut stats,
+      );
+    }
+    assert_eq!(3, stats.depths.len());
+    assert_eq!(1, stats.depths[0].nodes);
+    assert_eq!(0, stats.depths[0].prune_calls);
+    // The FU edge needs two moves, so every state after one move is pruned.
+    assert_eq!(1, stats.depths[1].nodes);
+    assert_eq!(stats.depths[1].prune_calls, stats.depths[1].prune_hits);
+    assert!(stats.depths[2].nodes > 1);
+    assert!(stats.depths[2].prune_rate() > 0.0);
+    assert!(stats.depths.iter().all(|d| d.time.is_none()));
+
+    let total = stats.total();
+    assert_eq!(
+      stats.depths.iter().map(|d| d.nodes).sum::<u64>(),
+      total.nodes
+    );
   }
 
   #[test]
@@ -1,6 +1,8 @@
 use cube::{Cube, Face};
+use std::marker::PhantomData;
 
 /// An `Index` represents a set of pieces of a `Cube`.
+#[allow(clippy::wrong_self_convention)]
 pub trait Index {
   /// The number of elements in this `Index`.
   const NUM_ELEMS: u32;
@@ -74,7 +76,7 @@ pub fn generic_edge_index_decode(
   // Extract the digits from the index.
   for _ in 0..num {
     edges.push(index % edge_div);
-    index = index / edge_div;
+    index /= edge_div;
     edge_div += 2;
   }
 
@@ -151,7 +153,7 @@ pub fn generic_corner_index_decode(
   // Extract the digits from the index.
   for _ in 0..num {
     corners.push(index % corner_div);
-    index = index / corner_div;
+    index /= corner_div;
     corner_div += 3;
   }
 
@@ -181,6 +183,260 @@ pub fn generic_corner_index_decode(
   }
 }
 
+/// What a `PieceSetIndex` records about each of its pieces. The state of each
+/// piece is a value below `BASE`, and pieces can't share a value divided by
+/// `STEP`, unless `STEP` is 0.
+pub trait Tracking {
+  const EDGE_BASE: u32;
+  const EDGE_STEP: u32;
+  const CORNER_BASE: u32;
+  const CORNER_STEP: u32;
+
+  /// Get the value of edge `e`.
+  fn edge(c: &Cube, e: (Face, Face)) -> u32;
+
+  /// Change the stickers of `c` so edge `e` has `value`.
+  fn set_edge(c: &mut Cube, e: (Face, Face), value: u32);
+
+  /// Get the value of corner `e`.
+  fn corner(c: &Cube, e: (Face, Face, Face)) -> u32;
+
+  /// Change the stickers of `c` so corner `e` has `value`.
+  fn set_corner(c: &mut Cube, e: (Face, Face, Face), value: u32);
+
+  /// The cube that `from_index` fills in.
+  fn blank() -> Cube {
+    Cube::invalid()
+  }
+}
+
+/// Track where each piece is and how it is oriented.
+#[derive(Clone, Copy, Debug)]
+pub struct Full;
+
+impl Tracking for Full {
+  const EDGE_BASE: u32 = 24;
+  const EDGE_STEP: u32 = 2;
+  const CORNER_BASE: u32 = 24;
+  const CORNER_STEP: u32 = 3;
+
+  fn edge(c: &Cube, e: (Face, Face)) -> u32 {
+    c.find_edge(e.0, e.1) as u32
+  }
+
+  fn set_edge(c: &mut Cube, e: (Face, Face), value: u32) {
+    c.edges[value as usize] = e.0;
+    c.edges[value as usize ^ 1] = e.1;
+  }
+
+  fn corner(c: &Cube, e: (Face, Face, Face)) -> u32 {
+    c.find_corner(e.0, e.1, e.2) as u32
+  }
+
+  fn set_corner(c: &mut Cube, e: (Face, Face, Face), value: u32) {
+    let (pos, o) = (value as usize / 3 * 3, value as usize % 3);
+    c.corners[pos + o] = e.0;
+    c.corners[pos + (o + 1) % 3] = e.1;
+    c.corners[pos + (o + 2) % 3] = e.2;
+  }
+}
+
+/// Track where each piece is, ignoring its orientation.
+#[derive(Clone, Copy, Debug)]
+pub struct Permutation;
+
+impl Tracking for Permutation {
+  const EDGE_BASE: u32 = 12;
+  const EDGE_STEP: u32 = 1;
+  const CORNER_BASE: u32 = 8;
+  const CORNER_STEP: u32 = 1;
+
+  fn edge(c: &Cube, e: (Face, Face)) -> u32 {
+    c.find_edge(e.0, e.1) as u32 / 2
+  }
+
+  fn set_edge(c: &mut Cube, e: (Face, Face), value: u32) {
+    Full::set_edge(c, e, value * 2);
+  }
+
+  fn corner(c: &Cube, e: (Face, Face, Face)) -> u32 {
+    c.find_corner(e.0, e.1, e.2) as u32 / 3
+  }
+
+  fn set_corner(c: &mut Cube, e: (Face, Face, Face), value: u32) {
+    Full::set_corner(c, e, value * 3);
+  }
+}
+
+/// Track the orientation of the pieces in each position, ignoring which
+/// pieces they are. Unlike the other kinds, this follows positions rather
+/// than pieces, so the moves used with it must keep the positions among
+/// themselves, or every edge or corner must be listed.
+///
+/// An edge is oriented if its U or D sticker, or F or B sticker for E slice
+/// edges, is on the first facelet of its position. A corner's orientation is
+/// which facelet its U or D sticker is on.
+#[derive(Clone, Copy, Debug)]
+pub struct Orientation;
+
+impl Orientation {
+  fn edge_orientation(a: Face, b: Face) -> u32 {
+    use Face::*;
+    match (a, b) {
+      (U, _) | (D, _) => 0,
+      (F, _) | (B, _) if b != U && b != D => 0,
+      _ => 1,
+    }
+  }
+}
+
+impl Tracking for Orientation {
+  const EDGE_BASE: u32 = 2;
+  const EDGE_STEP: u32 = 0;
+  const CORNER_BASE: u32 = 3;
+  const CORNER_STEP: u32 = 0;
+
+  fn edge(c: &Cube, e: (Face, Face)) -> u32 {
+    let pos = Cube::solved().find_edge(e.0, e.1) as usize & !1;
+    Orientation::edge_orientation(c.edges[pos], c.edges[pos + 1])
+  }
+
+  fn set_edge(c: &mut Cube, e: (Face, Face), value: u32) {
+    let pos = Cube::solved().find_edge(e.0, e.1) as usize & !1;
+    let (a, b) = (c.edges[pos], c.edges[pos + 1]);
+    if Orientation::edge_orientation(a, b) != value {
+      c.edges.swap(pos, pos + 1);
+    }
+  }
+
+  fn corner(c: &Cube, e: (Face, Face, Face)) -> u32 {
+    let pos = Cube::solved().find_corner(e.0, e.1, e.2) as usize / 3 * 3;
+    c.corners[pos..pos + 3]
+      .iter()
+      .position(|&f| f == Face::U || f == Face::D)
+      .unwrap_or(0) as u32
+  }
+
+  fn set_corner(c: &mut Cube, e: (Face, Face, Face), value: u32) {
+    let pos = Cube::solved().find_corner(e.0, e.1, e.2) as usize / 3 * 3;
+    let o = Orientation::corner(c, e);
+    c.corners[pos..pos + 3].rotate_right(((value + 3 - o) % 3) as usize);
+  }
+
+  /// Every piece starts solved, so pieces that move into the listed
+  /// positions have a known orientation.
+  fn blank() -> Cube {
+    Cube::solved()
+  }
+}
+
+/// Get the number of states of `n` pieces, see `Tracking`.
+const fn num_states(base: u32, step: u32, n: usize) -> u32 {
+  let mut states = 1;
+  let mut i = 0;
+  while i < n {
+    states *= base - step * i as u32;
+    i += 1;
+  }
+  states
+}
+
+/// Combine the values of some pieces into a number, see `Tracking`.
+fn encode(values: &[u32], base: u32, step: u32) -> u32 {
+  let mut index = 0;
+  for (i, &v) in values.iter().enumerate() {
+    let smaller = values[..i].iter().filter(|&&w| w < v).count() as u32;
+    index = index * (base - step * i as u32) + v - step * smaller;
+  }
+  index
+}
+
+/// Split a number made by `encode` back into the values of `N` pieces.
+fn decode<const N: usize>(mut index: u32, base: u32, step: u32) -> [u32; N] {
+  let mut values = [0; N];
+  for i in (0..N).rev() {
+    let radix = base - step * i as u32;
+    values[i] = index % radix;
+    index /= radix;
+  }
+  // Each value is the rank of its position amongst the unused ones, unless
+  // only orientations are tracked.
+  let mut used = 0u32;
+  for v in &mut values {
+    if let Some(rank) = v.checked_div(step) {
+      let pos = (0..)
+        .filter(|p| used & (1 << p) == 0)
+        .nth(rank as usize)
+        .unwrap();
+      used |= 1 << pos;
+      *v = pos * step + *v % step;
+    }
+  }
+  values
+}
+
+/// An `Index` for a list of `E` edges and `C` corners, each named by its
+/// stickers in the solved cube, e.g. `(Face::D, Face::L)`. What it records
+/// about each piece is chosen by `T`, see `Full`, `Permutation` and
+/// `Orientation`.
+#[derive(Clone, Copy, Debug)]
+pub struct PieceSetIndex<const E: usize, const C: usize, T = Full> {
+  edges: [(Face, Face); E],
+  corners: [(Face, Face, Face); C],
+  tracking: PhantomData<T>,
+}
+
+/// A `PieceSetIndex` of where the pieces are.
+pub type PermutationIndex<const E: usize, const C: usize> =
+  PieceSetIndex<E, C, Permutation>;
+
+/// A `PieceSetIndex` of how the pieces in some positions are oriented.
+pub type OrientationIndex<const E: usize, const C: usize> =
+  PieceSetIndex<E, C, Orientation>;
+
+impl<const E: usize, const C: usize, T> PieceSetIndex<E, C, T> {
+  pub const fn new(
+    edges: [(Face, Face); E],
+    corners: [(Face, Face, Face); C],
+  ) -> Self {
+    PieceSetIndex {
+      edges,
+      corners,
+      tracking: PhantomData,
+    }
+  }
+}
+
+impl<const E: usize, const C: usize, T: Tracking> Index
+  for PieceSetIndex<E, C, T>
+{
+  const NUM_ELEMS: u32 = num_states(T::EDGE_BASE, T::EDGE_STEP, E)
+    * num_states(T::CORNER_BASE, T::CORNER_STEP, C);
+
+  fn from_cube(&self, c: &Cube) -> u32 {
+    let edges = self.edges.map(|e| T::edge(c, e));
+    let corners = self.corners.map(|e| T::corner(c, e));
+    let num_corners = num_states(T::CORNER_BASE, T::CORNER_STEP, C);
+    encode(&edges, T::EDGE_BASE, T::EDGE_STEP) * num_corners
+      + encode(&corners, T::CORNER_BASE, T::CORNER_STEP)
+  }
+
+  fn from_index(&self, i: u32) -> Cube {
+    let num_corners = num_states(T::CORNER_BASE, T::CORNER_STEP, C);
+    let edges: [u32; E] = decode(i / num_corners, T::EDGE_BASE, T::EDGE_STEP);
+    let corners: [u32; C] =
+      decode(i % num_corners, T::CORNER_BASE, T::CORNER_STEP);
+    let mut c = T::blank();
+    for (&e, v) in self.edges.iter().zip(edges) {
+      T::set_edge(&mut c, e, v);
+    }
+    for (&e, v) in self.corners.iter().zip(corners) {
+      T::set_corner(&mut c, e, v);
+    }
+    c
+  }
+}
+
 #[cfg(test)]
 pub mod example {
   use super::*;
@@ -201,6 +457,34 @@ pub mod example {
       c
     }
   }
+
+  // Simple Index that tracks UL only.
+  pub struct UL;
+
+  impl Index for UL {
+    const NUM_ELEMS: u32 = 24;
+    fn from_cube(&self, c: &Cube) -> u32 {
+      c.find_edge(Face::U, Face::L) as u32
+    }
+
+    fn from_index(&self, i: u32) -> Cube {
+      let mut c = Cube::invalid();
+      c.edges[i as usize] = Face::U;
+      c.edges[i as usize ^ 1] = Face::L;
+      c
+    }
+  }
+
+  // The U layer corners.
+  pub const UL_CORNERS: PieceSetIndex<0, 4> = PieceSetIndex::new(
+    [],
+    [
+      (Face::U, Face::R, Face::F),
+      (Face::U, Face::F, Face::L),
+      (Face::U, Face::L, Face::B),
+      (Face::U, Face::B, Face::R),
+    ],
+  );
 }
 
 #[cfg(test)]
@@ -212,6 +496,7 @@ mod tests {
   #[test]
   fn exhaustive_uf() {
     exhaustive_index_check(&example::UF);
+    exhaustive_index_check(&example::UL);
   }
 
   #[test]
@@ -473,4 +758,94 @@ mod tests {
       assert_eq!(Face::L, c.corners[CornerPos::LUF as usize]);
     }
   }
+
+  #[test]
+  fn piece_set_index() {
+    use Face::*;
+    let edges = [(D, L), (F, L), (B, L)];
+    let corners = [(U, R, F), (U, F, L), (U, L, B), (U, B, R)];
+    let edge_index = PieceSetIndex::<3, 0>::new(edges, []);
+    let corner_index = PieceSetIndex::<0, 4>::new([], corners);
+    assert_eq!(24 * 22 * 20, PieceSetIndex::<3, 0>::NUM_ELEMS);
+    assert_eq!(24 * 21 * 18 * 15, PieceSetIndex::<0, 4>::NUM_ELEMS);
+    assert_eq!(24 * 22 * 24 * 21, PieceSetIndex::<2, 2>::NUM_ELEMS);
+
+    // The same numbering as the generic functions.
+    let mut c = Cube::solved();
+    for m in cube::parse_moves("R U' F2 L D' B R2 U M' x").unwrap() {
+      c.do_move(m);
+      assert_eq!(generic_edge_index(&c, &edges), edge_index.from_cube(&c));
+      assert_eq!(
+        generic_corner_index(&c, &corners),
+        corner_index.from_cube(&c)
+      );
+    }
+
+    exhaustive_index_check(&edge_index);
+    exhaustive_index_check(&PieceSetIndex::<2, 2>::new(
+      [(U, F), (R, B)],
+      [(D, F, R), (U, L, B)],
+    ));
+  }
+
+  #[test]
+  fn permutation_index() {
+    use Face::*;
+    assert_eq!(12 * 11 * 10 * 8 * 7, PermutationIndex::<3, 2>::NUM_ELEMS);
+    let index = PermutationIndex::<3, 2>::new(
+      [(U, F), (L, B), (D, R)],
+      [(U, R, F), (D, B, L)],
+    );
+    exhaustive_index_check(&index);
+
+    // Twisting and flipping pieces in place doesn't change the index.
+    let mut c = Cube::solved();
+    c.do_moves(&cube::parse_moves("R U R' U' F2 L").unwrap());
+    let mut twisted = c;
+    let uf = c.find_edge(U, F) as usize;
+    twisted.edges.swap(uf, uf ^ 1);
+    let urf = c.find_corner(U, R, F) as usize / 3 * 3;
+    twisted.corners[urf..urf + 3].rotate_left(1);
+    assert_eq!(index.from_cube(&c), index.from_cube(&twisted));
+  }
+
+  #[test]
+  fn orientation_index() {
+    use crate::move_set::MoveSet;
+    use crate::pruning::gen_prune_table;
+    use crate::transition::gen_transition_table;
+    use Face::*;
+    let edges = [
+      (U, F),
+      (U, L),
+      (U, B),
+      (U, R),
+      (D, F),
+      (D, L),
+      (D, B),
+      (D, R),
+      (F, R),
+      (F, L),
+      (B, L),
+      (B, R),
+    ];
+    let eo = OrientationIndex::<12, 0>::new(edges, []);
+    assert_eq!(4096, OrientationIndex::<12, 0>::NUM_ELEMS);
+    exhaustive_index_check(&eo);
+    exhaustive_index_check(&OrientationIndex::<2, 3>::new(
+      [(F, R), (D, B)],
+      [(U, R, F), (D, F, R), (D, L, F)],
+    ));
+
+    let mut c = Cube::solved();
+    c.do_moves(&cube::parse_moves("R U L2 D' B2").unwrap());
+    assert_eq!(0, eo.from_cube(&c));
+    c.do_moves(&cube::parse_moves("F").unwrap());
+    assert_ne!(0, eo.from_cube(&c));
+
+    // Only an even number of edges can be flipped.
+    let table = gen_transition_table(&eo, &MoveSet::faces());
+    let ptable = gen_prune_table(&table, 0);
+    assert_eq!(2048, ptable.iter().filter(|&&d| d != u8::MAX).count());
+  }
 }
@@ -1,4 +1,12 @@
+pub mod bidirectional;
+pub mod heuristic;
 pub mod iddfs;
 pub mod index;
+pub mod move_set;
+pub mod parallel;
 pub mod pruning;
+pub mod solve;
+pub mod stats;
+pub mod symmetry;
+pub mod table_file;
 pub mod transition;
@@ -0,0 +1,177 @@
+use cube::{parse_moves, Move, ParseError};
+use std::ops::Range;
+
+/// The moves that a search is allowed to use, such as `<U, M>` or
+/// `<R, r, U, M>`.
+///
+/// Each generator is a quarter or half turn, and the search uses every power
+/// of it. For example `R` gives `R`, `R2` and `R'`, but `R2` only gives `R2`.
+/// Generators are numbered by their position in the set, which is the order
+/// of the columns of a transition table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveSet(Vec<Move>);
+
+impl MoveSet {
+  /// Create a move set from its generators.
+  ///
+  /// Panics if a generator is not a quarter or half turn.
+  pub fn new(moves: Vec<Move>) -> MoveSet {
+    assert!(moves.iter().all(|m| m.amount() == 1 || m.amount() == 2));
+    MoveSet(moves)
+  }
+
+  /// The six face turns, `<U, D, F, B, R, L>`.
+  pub fn faces() -> MoveSet {
+    use cube::Face::*;
+    MoveSet::new(
+      [U, D, F, B, R, L]
+        .iter()
+        .map(|&f| Move::Face(f, 1))
+        .collect(),
+    )
+  }
+
+  /// Get the generators.
+  pub fn moves(&self) -> &[Move] {
+    &self.0
+  }
+
+  /// Get the number of generators.
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  /// Test if there are no generators.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Get the number of times generator `i` can be repeated before it undoes
+  /// itself, e.g. 3 for `R` and 1 for `R2`.
+  pub fn powers(&self, i: usize) -> u8 {
+    4 / self.0[i].amount() - 1
+  }
+
+  /// Get the move made by doing generator `i` `n` times.
+  pub fn power(&self, i: usize, n: u8) -> Move {
+    let m = self.0[i];
+    m.with_amount(n * m.amount())
+  }
+
+  /// Get the generator that `m` is a power of, if any.
+  pub fn position(&self, m: &Move) -> Option<usize> {
+    self.0.iter().position(|g| g.is_same_movement(m))
+  }
+}
+
+/// The reason a move set could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveSetError {
+  /// The generators aren't valid notation.
+  Parse(ParseError),
+  /// A generator that isn't a quarter or half turn, e.g. `R'`, and the byte
+  /// range of the input it is in.
+  UnexpectedMove(Move, Range<usize>),
+}
+
+impl std::fmt::Display for MoveSetError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      MoveSetError::Parse(e) => write!(f, "{}", e),
+      MoveSetError::UnexpectedMove(m, span) => {
+        write!(f, "unexpected move {} at position {}", m, span.start)
+      }
+    }
+  }
+}
+
+impl std::error::Error for MoveSetError {}
+
+impl From<ParseError> for MoveSetError {
+  fn from(e: ParseError) -> Self {
+    MoveSetError::Parse(e)
+  }
+}
+
+impl std::str::FromStr for MoveSet {
+  type Err = MoveSetError;
+
+  /// Parse a move set from its generators, e.g. `"R r U M"`. Each
+  /// generator must be a quarter or half turn.
+  fn from_str(s: &str) -> Result<MoveSet, MoveSetError> {
+    let moves = parse_moves(s)?;
+    match moves.iter().find(|m| m.amount() == 3) {
+      None => Ok(MoveSet::new(moves)),
+      Some(&m) => {
+        // Point at the first word with the move, or everything if the move
+        // is only made by a group.
+        let span = s
+          .split_whitespace()
+          .find(|w| parse_moves(w).is_ok_and(|w| w.contains(&m)))
+          .map_or(0..s.len(), |w| {
+            let start = w.as_ptr() as usize - s.as_ptr() as usize;
+            start..start + w.len()
+          });
+        Err(MoveSetError::UnexpectedMove(m, span))
+      }
+    }
+  }
+}
+
+impl std::fmt::Display for MoveSet {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "<")?;
+    for (i, m) in self.0.iter().enumerate() {
+      if i > 0 {
+        write!(f, ", ")?;
+      }
+      write!(f, "{}", m)?;
+    }
+    write!(f, ">")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cube::{Face, ParseErrorKind, Slice};
+
+  #[test]
+  fn move_set() {
+    let ms: MoveSet = "R r U M".parse().unwrap();
+    assert_eq!(4, ms.len());
+    assert_eq!("<R, r, U, M>", ms.to_string());
+    assert_eq!(3, ms.powers(0));
+    assert_eq!(Move::Slice(Slice::M, 3), ms.power(3, 3));
+    assert_eq!(Some(2), ms.position(&Move::Face(Face::U, 2)));
+    assert_eq!(None, ms.position(&Move::Face(Face::D, 1)));
+
+    let ms: MoveSet = "U D R2 L2 F2 B2".parse().unwrap();
+    assert_eq!(1, ms.powers(2));
+    assert_eq!(Move::Face(Face::R, 2), ms.power(2, 1));
+
+    assert_eq!("<U, D, F, B, R, L>", MoveSet::faces().to_string());
+  }
+
+  #[test]
+  fn parse_errors() {
+    let err = |s: &str| s.parse::<MoveSet>().unwrap_err();
+    let r3 = Move::Face(Face::R, 3);
+    assert_eq!(MoveSetError::UnexpectedMove(r3, 4..6), err("U M R' D"));
+    assert_eq!(MoveSetError::UnexpectedMove(r3, 2..4), err("U R3"));
+    assert_eq!(
+      MoveSetError::Parse(ParseError {
+        kind: ParseErrorKind::UnexpectedChar('Q'),
+        span: 2..3
+      }),
+      err("R Q")
+    );
+    assert_eq!("unexpected move R' at position 0", err("R'").to_string());
+  }
+
+  #[test]
+  #[should_panic]
+  fn invalid_generator() {
+    MoveSet::new(vec![Move::Face(Face::R, 3)]);
+  }
+}
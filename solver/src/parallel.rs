@@ -0,0 +1,312 @@
+//! Searching on several threads.
+//!
+//! Results don't depend on the number of threads or how the work is split
+//! between them: `parallel_solve` finds the same solution as `Solver::solve`,
+//! and `solve_each` returns its results in the order of its states.
+
+use crate::iddfs::{search, Control, IDDFSInfo};
+use crate::solve::{
+  SearchStats, SolveOptions, SolveResult, Solver, StopReason,
+};
+use cube::Move;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// How often, in nodes, to check the limits and the other threads.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Get the number of threads to use for `threads`, where 0 means one per
+/// core.
+fn num_threads(threads: usize) -> usize {
+  if threads > 0 {
+    threads
+  } else {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+  }
+}
+
+/// Run `f` on `threads` threads, calling it with each of `0..tasks` once.
+fn run<F: Fn(usize) + Sync>(tasks: usize, threads: usize, f: F) {
+  let next = AtomicUsize::new(0);
+  std::thread::scope(|s| {
+    for _ in 0..num_threads(threads).min(tasks) {
+      s.spawn(|| loop {
+        let task = next.fetch_add(1, Ordering::Relaxed);
+        if task >= tasks {
+          break;
+        }
+        f(task);
+      });
+    }
+  });
+}
+
+/// Solve each of `states` with `Solver::solve`, spreading them over
+/// `threads` threads, or one per core if `threads` is 0. The limits in
+/// `options` apply to each state separately, except that setting
+/// `options.cancel` stops every search.
+pub fn solve_each<I>(
+  info: &I,
+  states: &[I::State],
+  options: &SolveOptions,
+  threads: usize,
+) -> Vec<SolveResult>
+where
+  I: IDDFSInfo + Sync,
+  I::State: Send + Sync,
+{
+  let results = Mutex::new(vec![None; states.len()]);
+  run(states.len(), threads, |i| {
+    let result = Solver::new(info, options.clone()).solve(states[i]);
+    results.lock().unwrap()[i] = Some(result);
+  });
+  results
+    .into_inner()
+    .unwrap()
+    .into_iter()
+    .map(Option::unwrap)
+    .collect()
+}
+
+/// State shared by the threads of `parallel_solve`.
+struct Shared<'a> {
+  options: &'a SolveOptions,
+  start: Option<Instant>,
+  nodes: AtomicU64,
+  stop: Mutex<Option<StopReason>>,
+  stopped: AtomicBool,
+  /// The first subtree that has a solution.
+  best: AtomicUsize,
+}
+
+impl Shared<'_> {
+  fn stop(&self, reason: StopReason) {
+    self.stop.lock().unwrap().get_or_insert(reason);
+    self.stopped.store(true, Ordering::Relaxed);
+  }
+
+  /// Check the limits after `nodes` more nodes were visited.
+  fn check(&self, nodes: u64) {
+    let total = self.nodes.fetch_add(nodes, Ordering::Relaxed) + nodes;
+    if self.options.node_limit.is_some_and(|n| total >= n) {
+      self.stop(StopReason::NodeLimit);
+    }
+    if let (Some(start), Some(timeout)) = (self.start, self.options.timeout) {
+      if start.elapsed() >= timeout {
+        self.stop(StopReason::Timeout);
+      }
+    }
+    if let Some(cancel) = &self.options.cancel {
+      if cancel.load(Ordering::Relaxed) {
+        self.stop(StopReason::Cancelled);
+      }
+    }
+  }
+}
+
+/// Searches one subtree, stopping when a limit is reached or an earlier
+/// subtree has a solution.
+struct Subtree<'a, 'b> {
+  shared: &'a Shared<'b>,
+  task: usize,
+  nodes: u64,
+}
+
+impl Control for Subtree<'_, '_> {
+  fn node(&mut self) -> bool {
+    self.nodes += 1;
+    if self.nodes == CHECK_INTERVAL {
+      self.shared.check(self.nodes);
+      self.nodes = 0;
+    }
+    self.shared.stopped.load(Ordering::Relaxed)
+      || self.shared.best.load(Ordering::Relaxed) < self.task
+  }
+
+  fn solved(&mut self, _: &[Move]) -> bool {
+    true
+  }
+}
+
+/// Search for the shortest solution for `state` like `Solver::solve`, on
+/// `threads` threads, or one per core if `threads` is 0. Each depth is split
+/// into the subtrees after the first move, which are searched in parallel.
+///
+/// The limits in `options` are shared by every thread, and are checked every
+/// 1024 nodes, so the search may visit a few more nodes than the limit.
+pub fn parallel_solve<I>(
+  info: &I,
+  state: I::State,
+  options: &SolveOptions,
+  threads: usize,
+) -> SolveResult
+where
+  I: IDDFSInfo + Sync,
+  I::State: Send + Sync,
+{
+  let shared = Shared {
+    options,
+    start: options.timeout.map(|_| Instant::now()),
+    nodes: AtomicU64::new(0),
+    stop: Mutex::new(None),
+    stopped: AtomicBool::new(false),
+    best: AtomicUsize::new(usize::MAX),
+  };
+  let stats = |depth| SearchStats {
+    nodes: shared.nodes.load(Ordering::Relaxed),
+    depth,
+  };
+
+  shared.check(1);
+  if info.is_solved(&state) {
+    return SolveResult {
+      solution: Some(vec![]),
+      stop: StopReason::Solved,
+      stats: stats(0),
+    };
+  }
+
+  let move_set = info.move_set();
+  for depth in 1..=options.max_depth {
+    // The first move of each subtree, and the state after it.
+    let mut tasks = Vec::new();
+    for i in 0..move_set.len() {
+      let mut next = state;
+      for n in 1..=move_set.powers(i) {
+        next = info.transition(&next, i);
+        if !info.prune(&next, depth - 1) {
+          tasks.push((move_set.power(i, n), next));
+        }
+      }
+    }
+
+    let solutions = Mutex::new(vec![None; tasks.len()]);
+    run(tasks.len(), threads, |task| {
+      if shared.best.load(Ordering::Relaxed) < task {
+        return;
+      }
+      let (m, next) = tasks[task];
+      let mut control = Subtree {
+        shared: &shared,
+        task,
+        nodes: 0,
+      };
+      let mut solution = vec![m];
+      let found = search(next, info, depth - 1, &mut solution, &mut control);
+      shared.check(control.nodes);
+      // The search also returns true when it was stopped early.
+      let stopped = shared.stopped.load(Ordering::Relaxed)
+        || shared.best.load(Ordering::Relaxed) < task;
+      if found && !stopped {
+        solutions.lock().unwrap()[task] = Some(solution);
+        shared.best.fetch_min(task, Ordering::Relaxed);
+      }
+    });
+    shared.check(1);
+
+    if let Some(stop) = *shared.stop.lock().unwrap() {
+      return SolveResult {
+        solution: None,
+        stop,
+        stats: stats(depth),
+      };
+    }
+    let best = shared.best.load(Ordering::Relaxed);
+    if best < tasks.len() {
+      return SolveResult {
+        solution: solutions.into_inner().unwrap()[best].take(),
+        stop: StopReason::Solved,
+        stats: stats(depth),
+      };
+    }
+  }
+
+  SolveResult {
+    solution: None,
+    stop: StopReason::MaxDepth,
+    stats: stats(options.max_depth),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::heuristic::{Combine, CombinedInfo, PatternDatabase};
+  use crate::index::example::{UF, UL};
+  use crate::move_set::MoveSet;
+  use cube::{parse_moves, Cube};
+  use std::sync::Arc;
+
+  fn info() -> CombinedInfo<2> {
+    let move_set: MoveSet = "U D F B R L M".parse().unwrap();
+    CombinedInfo::new(
+      [
+        PatternDatabase::new(UF, &move_set),
+        PatternDatabase::new(UL, &move_set),
+      ],
+      Combine::Max,
+    )
+  }
+
+  fn states(info: &CombinedInfo<2>) -> Vec<([u32; 2], [u8; 2])> {
+    ["", "R U F", "L' B2 D", "F R' U2 L", "M U' F2 B"]
+      .iter()
+      .map(|s| {
+        let mut c = Cube::solved();
+        c.do_moves(&parse_moves(s).unwrap());
+        info.get_state(&c)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn same_as_sequential() {
+    let info = info();
+    let options = SolveOptions::default();
+    for state in states(&info) {
+      let expected = Solver::new(&info, options.clone()).solve(state);
+      for &threads in &[1, 2, 4] {
+        let result = parallel_solve(&info, state, &options, threads);
+        assert_eq!(StopReason::Solved, result.stop);
+        assert_eq!(expected.solution, result.solution);
+        assert_eq!(expected.stats.depth, result.stats.depth);
+      }
+    }
+  }
+
+  #[test]
+  fn each() {
+    let info = info();
+    let options = SolveOptions::default();
+    let states = states(&info);
+    let results = solve_each(&info, &states, &options, 3);
+    assert_eq!(states.len(), results.len());
+    for (&state, result) in states.iter().zip(&results) {
+      let expected = Solver::new(&info, options.clone()).solve(state);
+      assert_eq!(expected.solution, result.solution);
+    }
+  }
+
+  #[test]
+  fn limits() {
+    let info = info();
+    let state = states(&info)[3];
+
+    let options = SolveOptions {
+      max_depth: 1,
+      ..Default::default()
+    };
+    let result = parallel_solve(&info, state, &options, 2);
+    assert_eq!(StopReason::MaxDepth, result.stop);
+
+    let options = SolveOptions {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Default::default()
+    };
+    let result = parallel_solve(&info, state, &options, 2);
+    assert_eq!(StopReason::Cancelled, result.stop);
+    let results = solve_each(&info, &[state, state], &options, 2);
+    assert!(results.iter().all(|r| r.stop == StopReason::Cancelled));
+  }
+}
@@ -1,20 +1,300 @@
-/// Generate a pruning table for the `Index` represented by `trans_table`.
+use crate::transition::TransitionTable;
+
+/// A pruning table, giving a lower bound on the number of moves needed to
+/// solve each index. Implementations store the distances in different ways,
+/// trading memory for the work needed to read them.
+///
+/// Unreachable indexes have a distance of `u8::MAX`.
+pub trait PruneTable: Sized {
+  /// Generate the table for the `Index` represented by `trans_table`, like
+  /// `gen_prune_table`.
+  fn generate(trans_table: &TransitionTable, init_index: u32) -> Self;
+
+  /// Get the distance of `index`, which is one move away from an index with
+  /// a distance of `prev`. This is cheap for every table, so searches should
+  /// keep track of the distance and use it for every move after the first.
+  fn next_distance(&self, prev: u8, index: u32) -> u8;
+
+  /// Get the distance of `index` without knowing the distance of a
+  /// neighbour. Tables that can't store every distance use `trans_table`,
+  /// which must be the table the pruning table was generated from.
+  fn distance(&self, trans_table: &TransitionTable, index: u32) -> u8;
+
+  /// Test if `index` can be reached from the initial index.
+  fn is_reachable(&self, index: u32) -> bool;
+
+  /// Get the bytes the table is stored in.
+  fn as_bytes(&self) -> &[u8];
+
+  /// Get the number of bytes a table for `len` indexes is stored in.
+  fn num_bytes(len: usize) -> usize;
+
+  /// Make a table from the bytes of a table generated from `init_index`,
+  /// which must have the length given by `num_bytes`.
+  fn from_bytes(bytes: Box<[u8]>, init_index: u32) -> Self;
+}
+
+impl PruneTable for Box<[u8]> {
+  fn generate(trans_table: &TransitionTable, init_index: u32) -> Self {
+    gen_prune_table(trans_table, init_index)
+  }
+
+  fn next_distance(&self, _: u8, index: u32) -> u8 {
+    self[index as usize]
+  }
+
+  fn distance(&self, _: &TransitionTable, index: u32) -> u8 {
+    self[index as usize]
+  }
+
+  fn is_reachable(&self, index: u32) -> bool {
+    self[index as usize] != u8::MAX
+  }
+
+  fn as_bytes(&self) -> &[u8] {
+    self
+  }
+
+  fn num_bytes(len: usize) -> usize {
+    len
+  }
+
+  fn from_bytes(bytes: Box<[u8]>, _: u32) -> Self {
+    bytes
+  }
+}
+
+/// Fill a pruning table breadth first from `init_index`. `visit` is called
+/// with each index reached and its depth, and returns `true` if the index
+/// hadn't been visited before.
+fn bfs<F: FnMut(u32, u8) -> bool>(
+  trans_table: &TransitionTable,
+  init_index: u32,
+  mut visit: F,
+) {
+  let move_set = trans_table.move_set();
+  visit(init_index, 0);
+  let mut frontier = vec![init_index];
+  let mut next = Vec::new();
+  let mut depth = 0;
+  while !frontier.is_empty() {
+    depth += 1;
+    assert!(depth < u8::MAX, "pruning table depth doesn't fit in a u8");
+    for &index in &frontier {
+      for i in 0..move_set.len() {
+        let mut new_index = index;
+        for _ in 0..move_set.powers(i) {
+          new_index = trans_table[new_index as usize][i];
+          if visit(new_index, depth) {
+            next.push(new_index);
+          }
+        }
+      }
+    }
+    std::mem::swap(&mut frontier, &mut next);
+    next.clear();
+  }
+}
+
+/// Generate a pruning table for the `Index` represented by `trans_table`,
+/// giving the fewest moves needed to reach each index from `init_index`.
+/// Indexes that can't be reached from `init_index` using the table's
+/// `MoveSet` are set to `u8::MAX`.
+///
+/// The table is filled breadth first, so every index is only expanded once.
 pub fn gen_prune_table(
-  trans_table: &[[u32; 7]],
+  trans_table: &TransitionTable,
+  init_index: u32,
+) -> Box<[u8]> {
+  let mut table = vec![u8::MAX; trans_table.len()];
+  bfs(trans_table, init_index, |index, depth| {
+    let new = table[index as usize] == u8::MAX;
+    if new {
+      table[index as usize] = depth;
+    }
+    new
+  });
+  table.into_boxed_slice()
+}
+
+/// A pruning table that stores each distance in 4 bits, using half the
+/// memory of a `u8` table. Distances over 14 are stored as 14, which is
+/// still a lower bound.
+pub struct NibbleTable {
+  table: Box<[u8]>,
+}
+
+impl NibbleTable {
+  const UNREACHABLE: u8 = 0xf;
+
+  fn get(&self, index: u32) -> u8 {
+    let shift = 4 * (index % 2);
+    (self.table[index as usize / 2] >> shift) & 0xf
+  }
+
+  fn set(&mut self, index: u32, v: u8) {
+    let shift = 4 * (index % 2);
+    let byte = &mut self.table[index as usize / 2];
+    *byte = (*byte & !(0xf << shift)) | (v << shift);
+  }
+}
+
+impl PruneTable for NibbleTable {
+  fn generate(trans_table: &TransitionTable, init_index: u32) -> Self {
+    let mut t = NibbleTable {
+      table: vec![0xff; trans_table.len().div_ceil(2)].into_boxed_slice(),
+    };
+    bfs(trans_table, init_index, |index, depth| {
+      let new = t.get(index) == Self::UNREACHABLE;
+      if new {
+        t.set(index, depth.min(Self::UNREACHABLE - 1));
+      }
+      new
+    });
+    t
+  }
+
+  fn next_distance(&self, _: u8, index: u32) -> u8 {
+    match self.get(index) {
+      Self::UNREACHABLE => u8::MAX,
+      v => v,
+    }
+  }
+
+  fn distance(&self, _: &TransitionTable, index: u32) -> u8 {
+    self.next_distance(0, index)
+  }
+
+  fn is_reachable(&self, index: u32) -> bool {
+    self.get(index) != Self::UNREACHABLE
+  }
+
+  fn as_bytes(&self) -> &[u8] {
+    &self.table
+  }
+
+  fn num_bytes(len: usize) -> usize {
+    len.div_ceil(2)
+  }
+
+  fn from_bytes(table: Box<[u8]>, _: u32) -> Self {
+    NibbleTable { table }
+  }
+}
+
+/// A pruning table that stores each distance modulo 3 in 2 bits, using a
+/// quarter of the memory of a `u8` table. Neighbouring indexes are at most
+/// one move apart, so the distance of an index can be recovered from the
+/// distance of the index before it.
+pub struct Mod3Table {
+  table: Box<[u8]>,
+  init_index: u32,
+}
+
+impl Mod3Table {
+  const UNREACHABLE: u8 = 3;
+
+  fn get(&self, index: u32) -> u8 {
+    let shift = 2 * (index % 4);
+    (self.table[index as usize / 4] >> shift) & 3
+  }
+
+  fn set(&mut self, index: u32, v: u8) {
+    let shift = 2 * (index % 4);
+    let byte = &mut self.table[index as usize / 4];
+    *byte = (*byte & !(3 << shift)) | (v << shift);
+  }
+}
+
+impl PruneTable for Mod3Table {
+  fn generate(trans_table: &TransitionTable, init_index: u32) -> Self {
+    let mut t = Mod3Table {
+      table: vec![0xff; trans_table.len().div_ceil(4)].into_boxed_slice(),
+      init_index,
+    };
+    bfs(trans_table, init_index, |index, depth| {
+      let new = t.get(index) == Self::UNREACHABLE;
+      if new {
+        t.set(index, depth % 3);
+      }
+      new
+    });
+    t
+  }
+
+  fn next_distance(&self, prev: u8, index: u32) -> u8 {
+    let v = self.get(index);
+    if v == Self::UNREACHABLE {
+      return u8::MAX;
+    }
+    // The distance is one of prev - 1, prev and prev + 1, and only one of
+    // them has the right value modulo 3.
+    (prev.saturating_sub(1)..=prev + 1)
+      .find(|d| d % 3 == v)
+      .unwrap()
+  }
+
+  /// Find the distance by following indexes with smaller distances back to
+  /// the initial index, so this takes a move per step of the distance.
+  fn distance(&self, trans_table: &TransitionTable, index: u32) -> u8 {
+    if self.get(index) == Self::UNREACHABLE {
+      return u8::MAX;
+    }
+    let move_set = trans_table.move_set();
+    let mut index = index;
+    let mut distance = 0;
+    while index != self.init_index {
+      let closer = (self.get(index) + 2) % 3;
+      index = (0..move_set.len())
+        .flat_map(|i| {
+          (0..move_set.powers(i)).scan(index, move |next, _| {
+            *next = trans_table[*next as usize][i];
+            Some(*next)
+          })
+        })
+        .find(|&next| self.get(next) == closer)
+        .unwrap();
+      distance += 1;
+    }
+    distance
+  }
+
+  fn is_reachable(&self, index: u32) -> bool {
+    self.get(index) != Self::UNREACHABLE
+  }
+
+  fn as_bytes(&self) -> &[u8] {
+    &self.table
+  }
+
+  fn num_bytes(len: usize) -> usize {
+    len.div_ceil(4)
+  }
+
+  fn from_bytes(table: Box<[u8]>, init_index: u32) -> Self {
+    Mod3Table { table, init_index }
+  }
+}
+
+/// Generate a pruning table by a depth first search to `max_depth` moves.
+/// `max_depth` must be at least the depth of the deepest index, otherwise
+/// the deeper indexes are left as `u8::MAX`. This is slower than
+/// `gen_prune_table`, which should be used instead.
+pub fn gen_prune_table_dfs(
+  trans_table: &TransitionTable,
   max_depth: u32,
   init_index: u32,
 ) -> Box<[u8]> {
-  let mut table = vec![std::u8::MAX; trans_table.len()];
+  let mut table = vec![u8::MAX; trans_table.len()];
   gen_prune_table_inner(init_index, &mut table, trans_table, max_depth + 1, 0);
-  debug_assert!(table.iter().all(|&v| v != std::u8::MAX));
-  debug_assert!(table.iter().all(|&v| v < (max_depth + 1) as u8));
+  debug_assert!(table.iter().all(|&v| v == u8::MAX || v <= max_depth as u8));
   table.into_boxed_slice()
 }
 
 fn gen_prune_table_inner(
   index: u32,
   prune_table: &mut [u8],
-  trans_table: &[[u32; 7]],
+  trans_table: &TransitionTable,
   max_depth: u32,
   depth: u32,
 ) {
@@ -25,9 +305,10 @@ fn gen_prune_table_inner(
   }
   // Save the current depth for this index.
   prune_table[index as usize] = depth as u8;
-  for i in 0..7 {
+  let move_set = trans_table.move_set();
+  for i in 0..move_set.len() {
     let mut new_index = index;
-    for _ in 0..3 {
+    for _ in 0..move_set.powers(i) {
       new_index = trans_table[new_index as usize][i];
       gen_prune_table_inner(
         new_index,
@@ -51,9 +332,10 @@ mod tests {
   #[test]
   fn minimal_uf() {
     let uf = UF;
-    let table = gen_transition_table(&uf);
-    let ptable = gen_prune_table(&table, 2, 0);
+    let table = gen_transition_table(&uf, &"U D F B R L M".parse().unwrap());
+    let ptable = gen_prune_table(&table, 0);
     assert_eq!(2, *ptable.iter().max().unwrap());
+    assert_eq!(ptable, gen_prune_table_dfs(&table, 2, 0));
 
     let mut c = Cube::solved();
     assert_eq!(0, ptable[uf.from_cube(&c) as usize]);
@@ -66,4 +348,45 @@ mod tests {
     c.do_move(Move::Face(cube::Face::B, 1));
     assert_eq!(1, ptable[uf.from_cube(&c) as usize]);
   }
+
+  #[test]
+  fn compact_tables() {
+    let table = gen_transition_table(&UF, &"U D F B R L M".parse().unwrap());
+    let init = UF.from_cube(&Cube::solved());
+    let ptable = gen_prune_table(&table, init);
+    let nibble = NibbleTable::generate(&table, init);
+    let mod3 = Mod3Table::generate(&table, init);
+    for i in 0..table.len() as u32 {
+      let d = ptable[i as usize];
+      assert_eq!(d, ptable.distance(&table, i));
+      assert_eq!(d, nibble.distance(&table, i));
+      assert_eq!(d, mod3.distance(&table, i));
+      for &next in &table[i as usize] {
+        let next_d = ptable[next as usize];
+        assert_eq!(next_d, nibble.next_distance(d, next));
+        assert_eq!(next_d, mod3.next_distance(d, next));
+      }
+    }
+
+    // Unreachable indexes.
+    let table = gen_transition_table(&UF, &"U2 D2 F2 B2".parse().unwrap());
+    let ptable = gen_prune_table(&table, init);
+    let nibble = NibbleTable::generate(&table, init);
+    let mod3 = Mod3Table::generate(&table, init);
+    for i in 0..table.len() as u32 {
+      assert_eq!(ptable[i as usize], nibble.distance(&table, i));
+      assert_eq!(ptable[i as usize], mod3.distance(&table, i));
+    }
+  }
+
+  #[test]
+  fn half_turns() {
+    // With only half turns, UF can only reach UF, UB, DF and DB.
+    let uf = UF;
+    let table = gen_transition_table(&uf, &"U2 D2 F2 B2".parse().unwrap());
+    let ptable = gen_prune_table(&table, 0);
+    let reached = ptable.iter().filter(|&&d| d != u8::MAX).count();
+    assert_eq!(4, reached);
+    assert_eq!(ptable, gen_prune_table_dfs(&table, 3, 0));
+  }
 }
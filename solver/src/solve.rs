@@ -0,0 +1,285 @@
+use crate::iddfs::{search, Control, IDDFSInfo};
+use crate::stats::Stats;
+use cube::Move;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often, in nodes, to check the timeout and the cancellation flag.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Limits on how long `Solver::solve` searches for.
+#[derive(Clone, Debug)]
+pub struct SolveOptions {
+  /// The longest solution to search for.
+  pub max_depth: usize,
+  /// The most nodes to visit, over every depth.
+  pub node_limit: Option<u64>,
+  /// The most time to spend searching. `Instant` is only used when this is
+  /// set, since it panics on wasm.
+  pub timeout: Option<Duration>,
+  /// Stop the search when this is set, e.g. from another thread.
+  pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for SolveOptions {
+  fn default() -> Self {
+    SolveOptions {
+      max_depth: 20,
+      node_limit: None,
+      timeout: None,
+      cancel: None,
+    }
+  }
+}
+
+/// Why the search stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+  /// A solution was found.
+  Solved,
+  /// There is no solution of up to `max_depth` moves.
+  MaxDepth,
+  NodeLimit,
+  Timeout,
+  Cancelled,
+}
+
+/// Counters for a search.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchStats {
+  /// The number of states visited.
+  pub nodes: u64,
+  /// The deepest depth that was searched.
+  pub depth: usize,
+}
+
+/// The result of `Solver::solve`.
+#[derive(Clone, Debug)]
+pub struct SolveResult {
+  /// The shortest solution, if one was found.
+  pub solution: Option<Vec<Move>>,
+  pub stop: StopReason,
+  pub stats: SearchStats,
+}
+
+/// Runs iterative deepening searches, stopping early when the limits in its
+/// `SolveOptions` are reached.
+pub struct Solver<'a, I: IDDFSInfo> {
+  info: &'a I,
+  options: SolveOptions,
+}
+
+impl<'a, I: IDDFSInfo> Solver<'a, I> {
+  pub fn new(info: &'a I, options: SolveOptions) -> Self {
+    Solver { info, options }
+  }
+
+  /// Search for the shortest solution for `state`.
+  pub fn solve(&self, state: I::State) -> SolveResult {
+    self.solve_with_stats(state, &mut ())
+  }
+
+  /// `solve`, recording statistics about each depth in `stats`.
+  pub fn solve_with_stats<S: Stats>(
+    &self,
+    state: I::State,
+    stats: &mut S,
+  ) -> SolveResult {
+    let mut budget = Budget {
+      options: &self.options,
+      start: self.options.timeout.map(|_| Instant::now()),
+      nodes: 0,
+      stop: None,
+      stats,
+    };
+    let mut solution = Vec::with_capacity(self.options.max_depth);
+
+    for depth in 0..=self.options.max_depth {
+      budget.stats.start_depth(depth);
+      let found = search(state, self.info, depth, &mut solution, &mut budget);
+      budget.stats.end_depth(depth);
+      let stats = SearchStats {
+        nodes: budget.nodes,
+        depth,
+      };
+      if let Some(stop) = budget.stop {
+        return SolveResult {
+          solution: None,
+          stop,
+          stats,
+        };
+      }
+      if found {
+        return SolveResult {
+          solution: Some(solution),
+          stop: StopReason::Solved,
+          stats,
+        };
+      }
+    }
+
+    SolveResult {
+      solution: None,
+      stop: StopReason::MaxDepth,
+      stats: SearchStats {
+        nodes: budget.nodes,
+        depth: self.options.max_depth,
+      },
+    }
+  }
+}
+
+/// Stops a search at the first solution or when a limit is reached.
+struct Budget<'a, S> {
+  options: &'a SolveOptions,
+  start: Option<Instant>,
+  nodes: u64,
+  stop: Option<StopReason>,
+  stats: &'a mut S,
+}
+
+impl<S> Budget<'_, S> {
+  fn check(&self) -> Option<StopReason> {
+    if self.options.node_limit.is_some_and(|n| self.nodes >= n) {
+      return Some(StopReason::NodeLimit);
+    }
+    if self.nodes % CHECK_INTERVAL != 0 {
+      return None;
+    }
+    if let (Some(start), Some(timeout)) = (self.start, self.options.timeout) {
+      if start.elapsed() >= timeout {
+        return Some(StopReason::Timeout);
+      }
+    }
+    if let Some(cancel) = &self.options.cancel {
+      if cancel.load(Ordering::Relaxed) {
+        return Some(StopReason::Cancelled);
+      }
+    }
+    None
+  }
+}
+
+impl<S: Stats> Control for Budget<'_, S> {
+  fn node(&mut self) -> bool {
+    self.stop = self.check();
+    if self.stop.is_none() {
+      self.nodes += 1;
+      self.stats.node();
+    }
+    self.stop.is_some()
+  }
+
+  fn pruned(&mut self, pruned: bool) {
+    self.stats.prune(pruned);
+  }
+
+  fn solved(&mut self, _: &[Move]) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::index::example::UF;
+  use crate::move_set::MoveSet;
+  use crate::stats::DepthStats;
+  use crate::transition::{gen_transition_table, TransitionTable};
+  use cube::sticker_cube::EdgePos;
+  use cube::{Face, Slice};
+
+  /// Solves the UF edge without pruning, so searches take many nodes.
+  struct UFInfo(TransitionTable);
+
+  impl IDDFSInfo for UFInfo {
+    type State = u32;
+
+    fn move_set(&self) -> &MoveSet {
+      self.0.move_set()
+    }
+
+    fn is_solved(&self, &s: &Self::State) -> bool {
+      s == 0
+    }
+
+    fn transition(&self, state: &Self::State, m: usize) -> Self::State {
+      self.0[*state as usize][m]
+    }
+
+    fn prune(&self, _: &Self::State, _: usize) -> bool {
+      false
+    }
+  }
+
+  fn uf_info(move_set: &str) -> UFInfo {
+    UFInfo(gen_transition_table(&UF, &move_set.parse().unwrap()))
+  }
+
+  #[test]
+  fn solve() {
+    let info = uf_info("U D F B R L M");
+    let result =
+      Solver::new(&info, SolveOptions::default()).solve(EdgePos::FU as u32);
+    assert_eq!(StopReason::Solved, result.stop);
+    assert_eq!(
+      Some(vec![Move::Face(Face::U, 2), Move::Slice(Slice::M, 1)]),
+      result.solution
+    );
+    assert_eq!(2, result.stats.depth);
+    assert!(result.stats.nodes > 0);
+
+    let result =
+      Solver::new(&info, SolveOptions::default()).solve(EdgePos::UF as u32);
+    assert_eq!(Some(vec![]), result.solution);
+  }
+
+  #[test]
+  fn limits() {
+    // <R, U> can't flip edges, so this searches until a limit is reached.
+    let info = uf_info("R U");
+    let state = EdgePos::FU as u32;
+
+    let options = SolveOptions {
+      max_depth: 6,
+      ..Default::default()
+    };
+    let result = Solver::new(&info, options).solve(state);
+    assert_eq!(StopReason::MaxDepth, result.stop);
+    assert_eq!(None, result.solution);
+    assert_eq!(6, result.stats.depth);
+
+    let options = SolveOptions {
+      node_limit: Some(1000),
+      ..Default::default()
+    };
+    let result = Solver::new(&info, options.clone()).solve(state);
+    assert_eq!(StopReason::NodeLimit, result.stop);
+    assert_eq!(1000, result.stats.nodes);
+
+    let mut stats = DepthStats::timed();
+    let result =
+      Solver::new(&info, options).solve_with_stats(state, &mut stats);
+    assert_eq!(result.stats.depth + 1, stats.depths.len());
+    assert_eq!(result.stats.nodes, stats.total().nodes);
+    assert!(stats.depths.iter().all(|d| d.time.is_some()));
+
+    let options = SolveOptions {
+      timeout: Some(Duration::from_millis(10)),
+      ..Default::default()
+    };
+    assert_eq!(
+      StopReason::Timeout,
+      Solver::new(&info, options).solve(state).stop
+    );
+
+    let options = SolveOptions {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Default::default()
+    };
+    let result = Solver::new(&info, options).solve(state);
+    assert_eq!(StopReason::Cancelled, result.stop);
+    assert_eq!(0, result.stats.nodes);
+  }
+}
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+/// Collects statistics about a search. Every method does nothing by default,
+/// and `()` is a collector that records nothing, so searches without
+/// statistics cost nothing extra.
+pub trait Stats {
+  /// Called when an iteration searching for solutions of `depth` moves
+  /// starts.
+  fn start_depth(&mut self, _depth: usize) {}
+
+  /// Called when the iteration for `depth` finishes.
+  fn end_depth(&mut self, _depth: usize) {}
+
+  /// Called for each state visited.
+  fn node(&mut self) {}
+
+  /// Called after each call to `IDDFSInfo::prune`, with its result.
+  fn prune(&mut self, _pruned: bool) {}
+}
+
+impl Stats for () {}
+
+/// Counters for one iteration of a search.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DepthStat {
+  /// The number of states visited.
+  pub nodes: u64,
+  /// The number of calls to `IDDFSInfo::prune`.
+  pub prune_calls: u64,
+  /// The number of those calls that pruned the state.
+  pub prune_hits: u64,
+  /// The time the iteration took, if timing is enabled.
+  pub time: Option<Duration>,
+}
+
+impl DepthStat {
+  /// The fraction of states that were pruned, or 0 if `prune` wasn't called.
+  pub fn prune_rate(&self) -> f64 {
+    if self.prune_calls == 0 {
+      0.0
+    } else {
+      self.prune_hits as f64 / self.prune_calls as f64
+    }
+  }
+}
+
+/// Records a `DepthStat` for each depth that was searched.
+#[derive(Clone, Debug, Default)]
+pub struct DepthStats {
+  /// The counters for each depth, indexed by the depth.
+  pub depths: Vec<DepthStat>,
+  timed: bool,
+  current: usize,
+  start: Option<Instant>,
+}
+
+impl DepthStats {
+  /// Create a collector that only counts nodes and pruning.
+  pub fn new() -> Self {
+    DepthStats::default()
+  }
+
+  /// Create a collector that also times each depth. This uses `Instant`, so
+  /// it panics on wasm.
+  pub fn timed() -> Self {
+    DepthStats {
+      timed: true,
+      ..DepthStats::default()
+    }
+  }
+
+  /// Get the sum of the counters over every depth.
+  pub fn total(&self) -> DepthStat {
+    let mut total = DepthStat::default();
+    for d in &self.depths {
+      total.nodes += d.nodes;
+      total.prune_calls += d.prune_calls;
+      total.prune_hits += d.prune_hits;
+      total.time = match (total.time, d.time) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+      };
+    }
+    total
+  }
+}
+
+impl Stats for DepthStats {
+  fn start_depth(&mut self, depth: usize) {
+    if self.depths.len() <= depth {
+      self.depths.resize(depth + 1, DepthStat::default());
+    }
+    self.current = depth;
+    if self.timed {
+      self.start = Some(Instant::now());
+    }
+  }
+
+  fn end_depth(&mut self, depth: usize) {
+    if let Some(start) = self.start.take() {
+      let stat = &mut self.depths[depth];
+      stat.time = Some(stat.time.unwrap_or_default() + start.elapsed());
+    }
+  }
+
+  fn node(&mut self) {
+    self.depths[self.current].nodes += 1;
+  }
+
+  fn prune(&mut self, pruned: bool) {
+    let stat = &mut self.depths[self.current];
+    stat.prune_calls += 1;
+    stat.prune_hits += pruned as u64;
+  }
+}
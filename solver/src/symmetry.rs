@@ -0,0 +1,614 @@
+//! Symmetry reduction of indexes.
+//!
+//! Rotating a cube, doing some moves and rotating it back gives a state that
+//! is as far from solved as the original, as long as the rotation keeps the
+//! index's pieces and the move set the same. Indexes that are the same up to
+//! such a rotation form a symmetry class, and a pruning table only needs one
+//! entry per class.
+//!
+//! A `SymTable` follows an index by its class and the symmetry that takes
+//! it to the class representative, so neither it nor the pruning table has
+//! an entry per index. A small index paired with it is conjugated by that
+//! symmetry with a `ConjTable` instead.
+//!
+//! Only the 24 rotations are supported. Mirror images would double the group
+//! to 48 symmetries, but they turn clockwise moves into anticlockwise ones,
+//! which conjugating doesn't handle, so a table shrinks by at most 24 times.
+
+use crate::index::Index;
+use crate::move_set::MoveSet;
+use crate::transition::TransitionTable;
+use cube::{Cube, Face, Move, Rotation};
+
+/// Test if two cubes have the same stickers.
+fn same(a: &Cube, b: &Cube) -> bool {
+  a.edges == b.edges && a.corners == b.corners && a.centres == b.centres
+}
+
+/// Conjugate `c` by the rotation `sym`. The cube is rotated by `sym` and then
+/// recoloured so its centres are back in their solved places, which gives
+/// the state that doing the inverse of `sym`, the moves that made `c`, and
+/// then `sym` would give.
+pub fn conjugate(c: &Cube, sym: &[Move]) -> Cube {
+  let mut rotated = Cube::solved();
+  rotated.do_moves(sym);
+  // The face each sticker colour is recoloured to, indexed by `Face`.
+  let mut colours = [Face::U; 6];
+  for (&from, &to) in rotated.centres.iter().zip(&Cube::solved().centres) {
+    colours[from as usize] = to;
+  }
+
+  let mut c = *c;
+  c.do_moves(sym);
+  for f in c
+    .edges
+    .iter_mut()
+    .chain(&mut c.corners)
+    .chain(&mut c.centres)
+  {
+    *f = colours[*f as usize];
+  }
+  c
+}
+
+/// A group of rotations to reduce an index by.
+#[derive(Clone, Debug)]
+pub struct SymmetryGroup {
+  syms: Vec<Vec<Move>>,
+  /// `products[s * syms.len() + t]` is the symmetry `s` followed by `t`.
+  products: Vec<usize>,
+}
+
+impl SymmetryGroup {
+  /// Create a group from its rotations, starting with the identity.
+  ///
+  /// Panics if the rotations aren't a group.
+  pub fn new(syms: Vec<Vec<Move>>) -> SymmetryGroup {
+    assert!(
+      syms[0].is_empty(),
+      "the first symmetry must be the identity"
+    );
+    let mut test = Cube::solved();
+    test.do_moves(&cube::parse_moves("R U F' D2 L M").unwrap());
+    let find = |c: &Cube| {
+      syms
+        .iter()
+        .position(|s| same(&conjugate(&test, s), c))
+        .expect("symmetries aren't closed")
+    };
+    let products = syms
+      .iter()
+      .flat_map(|s| syms.iter().map(move |t| [&s[..], t].concat()))
+      .map(|st| find(&conjugate(&test, &st)))
+      .collect();
+    SymmetryGroup { syms, products }
+  }
+
+  /// All 24 rotations of the cube.
+  pub fn rotations() -> SymmetryGroup {
+    let mut syms: Vec<Vec<Move>> = vec![vec![]];
+    let mut cubes = vec![Cube::solved()];
+    let mut i = 0;
+    while i < syms.len() {
+      for &r in &[Rotation::X, Rotation::Y] {
+        let mut c = cubes[i];
+        c.do_move(Move::Rotation(r, 1));
+        if !cubes.iter().any(|d| same(&c, d)) {
+          let mut sym = syms[i].clone();
+          sym.push(Move::Rotation(r, 1));
+          syms.push(sym);
+          cubes.push(c);
+        }
+      }
+      i += 1;
+    }
+    SymmetryGroup::new(syms)
+  }
+
+  /// The 4 rotations about the U-D axis, which keep the U and D layers.
+  pub fn y_rotations() -> SymmetryGroup {
+    SymmetryGroup::new(
+      (0..4)
+        .map(|n| (0..n).map(|_| Move::Rotation(Rotation::Y, 1)).collect())
+        .collect(),
+    )
+  }
+
+  /// The 8 rotations that keep the U-D axis, which keep the U and D layers
+  /// and the E slice.
+  pub fn ud_rotations() -> SymmetryGroup {
+    let x2 = Move::Rotation(Rotation::X, 2);
+    let y = Move::Rotation(Rotation::Y, 1);
+    SymmetryGroup::new(
+      (0..8)
+        .map(|n| {
+          let flip = if n < 4 { None } else { Some(x2) };
+          flip.into_iter().chain((0..n % 4).map(|_| y)).collect()
+        })
+        .collect(),
+    )
+  }
+
+  /// Get the rotations.
+  pub fn syms(&self) -> &[Vec<Move>] {
+    &self.syms
+  }
+
+  /// Get the number of rotations.
+  pub fn len(&self) -> usize {
+    self.syms.len()
+  }
+
+  /// Test if the group is empty, which it never is.
+  pub fn is_empty(&self) -> bool {
+    self.syms.is_empty()
+  }
+
+  /// Get the symmetry that conjugating by `s` and then by `t` is the same
+  /// as.
+  pub fn product(&self, s: usize, t: usize) -> usize {
+    self.products[s * self.len() + t]
+  }
+
+  /// Get the symmetry that undoes symmetry `s`.
+  pub fn inverse(&self, s: usize) -> usize {
+    (0..self.len()).find(|&t| self.product(s, t) == 0).unwrap()
+  }
+
+  /// Test if conjugating each generator of `move_set` by each symmetry gives
+  /// a move in `move_set`, so that conjugating a state doesn't change how
+  /// many moves it needs.
+  pub fn preserves(&self, move_set: &MoveSet) -> bool {
+    let moves: Vec<Cube> = (0..move_set.len())
+      .flat_map(|i| (1..=move_set.powers(i)).map(move |n| (i, n)))
+      .map(|(i, n)| {
+        let mut c = Cube::solved();
+        c.do_move(move_set.power(i, n));
+        c
+      })
+      .collect();
+    self.syms.iter().all(|s| {
+      moves
+        .iter()
+        .all(|m| moves.iter().any(|n| same(&conjugate(m, s), n)))
+    })
+  }
+}
+
+/// Panics if a rotation in `group` moves the pieces that `index` tracks to
+/// pieces it doesn't, which would make conjugating an index meaningless.
+/// Each generator power of `move_set` that leaves the index of the solved
+/// cube, or of a few decoded indexes, alone is checked to leave the index of
+/// the conjugated cube alone too.
+fn assert_keeps_pieces<I: Index>(
+  index: &I,
+  group: &SymmetryGroup,
+  move_set: &MoveSet,
+) {
+  let n = I::NUM_ELEMS;
+  let bases = [
+    Cube::solved(),
+    index.from_index(n / 2),
+    index.from_index(n - 1),
+  ];
+  for c in &bases {
+    for i in 0..move_set.len() {
+      for p in 1..=move_set.powers(i) {
+        let mut d = *c;
+        d.do_move(move_set.power(i, p));
+        if index.from_cube(c) != index.from_cube(&d) {
+          continue;
+        }
+        for s in group.syms() {
+          assert_eq!(
+            index.from_cube(&conjugate(c, s)),
+            index.from_cube(&conjugate(&d, s)),
+            "a symmetry changes the pieces of the index"
+          );
+        }
+      }
+    }
+  }
+}
+
+/// A sym-coordinate, a symmetry class and a symmetry. It stands for the index
+/// that conjugating by the symmetry takes to the representative of the
+/// class.
+pub type SymCoord = (u32, u8);
+
+/// The symmetry classes of an index, and a transition table for its
+/// sym-coordinates. Only the class representatives are stored, so the table
+/// is about as many times smaller than a `TransitionTable` as there are
+/// symmetries.
+pub struct SymTable {
+  group: SymmetryGroup,
+  move_set: MoveSet,
+  /// Every power of each generator, as the generator and the power.
+  moves: Vec<(usize, u8)>,
+  /// `conj_moves[s * move_set.len() + m]` is the position in `moves` of
+  /// generator `m` conjugated by symmetry `s`.
+  conj_moves: Box<[u8]>,
+  /// The smallest index of each class, in order.
+  reps: Box<[u32]>,
+  /// The symmetries that fix each representative, as a bit mask.
+  stabilisers: Box<[u32]>,
+  /// `trans[class * moves.len() + i]` is the sym-coordinate reached by doing
+  /// `moves[i]` to the representative of `class`.
+  trans: Box<[SymCoord]>,
+}
+
+impl SymTable {
+  /// Find the symmetry classes of `index` under `group`, and generate the
+  /// transition table for the generators of `move_set`.
+  ///
+  /// Panics if a rotation in `group` changes the set of pieces that `index`
+  /// tracks, or changes `move_set`.
+  pub fn new<I: Index>(
+    index: &I,
+    group: &SymmetryGroup,
+    move_set: &MoveSet,
+  ) -> SymTable {
+    assert!(group.len() <= 32, "too many symmetries for a bit mask");
+    assert!(group.preserves(move_set), "a symmetry changes the move set");
+    assert_keeps_pieces(index, group, move_set);
+
+    // The sym-coordinate of every index is only kept while generating the
+    // table, so each index is conjugated once.
+    let mut coords = vec![(u32::MAX, 0); I::NUM_ELEMS as usize];
+    let mut reps = Vec::new();
+    let mut stabilisers = Vec::new();
+    for i in 0..I::NUM_ELEMS {
+      if coords[i as usize].0 != u32::MAX {
+        continue;
+      }
+      // `i` is the smallest index of a new class.
+      let class = reps.len() as u32;
+      let c = index.from_index(i);
+      let mut stabiliser = 0;
+      for (s, sym) in group.syms().iter().enumerate() {
+        let j = index.from_cube(&conjugate(&c, sym));
+        if j == i {
+          stabiliser |= 1 << s;
+        }
+        if coords[j as usize].0 == u32::MAX {
+          coords[j as usize] = (class, group.inverse(s) as u8);
+        }
+      }
+      reps.push(i);
+      stabilisers.push(stabiliser);
+    }
+
+    let moves: Vec<(usize, u8)> = (0..move_set.len())
+      .flat_map(|i| (1..=move_set.powers(i)).map(move |n| (i, n)))
+      .collect();
+    let cubes: Vec<Cube> = moves
+      .iter()
+      .map(|&(i, n)| {
+        let mut c = Cube::solved();
+        c.do_move(move_set.power(i, n));
+        c
+      })
+      .collect();
+    let conj_moves = group
+      .syms()
+      .iter()
+      .flat_map(|s| (0..move_set.len()).map(move |m| (s, m)))
+      .map(|(s, m)| {
+        let c = conjugate(
+          &cubes[moves.iter().position(|&g| g == (m, 1)).unwrap()],
+          s,
+        );
+        cubes.iter().position(|d| same(&c, d)).unwrap() as u8
+      })
+      .collect();
+
+    let mut trans = Vec::with_capacity(reps.len() * moves.len());
+    for &rep in &reps {
+      let c = index.from_index(rep);
+      for &(i, n) in &moves {
+        let mut c = c;
+        c.do_move(move_set.power(i, n));
+        trans.push(coords[index.from_cube(&c) as usize]);
+      }
+    }
+
+    SymTable {
+      group: group.clone(),
+      move_set: move_set.clone(),
+      moves,
+      conj_moves,
+      reps: reps.into_boxed_slice(),
+      stabilisers: stabilisers.into_boxed_slice(),
+      trans: trans.into_boxed_slice(),
+    }
+  }
+
+  /// Get the symmetry group.
+  pub fn group(&self) -> &SymmetryGroup {
+    &self.group
+  }
+
+  /// Get the move set the table was generated for.
+  pub fn move_set(&self) -> &MoveSet {
+    &self.move_set
+  }
+
+  /// Get the number of symmetry classes.
+  pub fn num_classes(&self) -> usize {
+    self.reps.len()
+  }
+
+  /// Get the representative index of `class`.
+  pub fn representative(&self, class: u32) -> u32 {
+    self.reps[class as usize]
+  }
+
+  /// Test if symmetry `s` fixes the representative of `class`.
+  pub fn fixes(&self, class: u32, s: usize) -> bool {
+    self.stabilisers[class as usize] & 1 << s != 0
+  }
+
+  /// Get the sym-coordinate of `c` for `index`, which must be the index the
+  /// table was generated for.
+  pub fn sym_coord<I: Index>(&self, index: &I, c: &Cube) -> SymCoord {
+    let (rep, s) = self
+      .group
+      .syms()
+      .iter()
+      .enumerate()
+      .map(|(s, sym)| (index.from_cube(&conjugate(c, sym)), s))
+      .min()
+      .unwrap();
+    let class = self.reps.binary_search(&rep).expect("not a representative");
+    (class as u32, s as u8)
+  }
+
+  /// Get the sym-coordinate reached by doing generator `m` from `coord`.
+  pub fn transition(&self, (class, sym): SymCoord, m: usize) -> SymCoord {
+    // Conjugating the index by `sym` gives the representative, so doing `m`
+    // to it is the same as doing the conjugated move to the representative.
+    let i = self.conj_moves[sym as usize * self.move_set.len() + m] as usize;
+    let (class, t) = self.trans[class as usize * self.moves.len() + i];
+    (class, self.group.product(sym as usize, t as usize) as u8)
+  }
+}
+
+/// A table to conjugate an index by each symmetry of a group. This is for
+/// the small index that a `SymTable`'s index is paired with, which is
+/// conjugated by the symmetry of the sym-coordinate.
+pub struct ConjTable {
+  num_syms: usize,
+  /// `conj[i * num_syms + s]` is index `i` conjugated by symmetry `s`.
+  conj: Box<[u32]>,
+}
+
+impl ConjTable {
+  /// Generate the table for `index`, to pair with the index of `sym_table`.
+  ///
+  /// Panics if a rotation in the group of `sym_table` changes the set of
+  /// pieces that `index` tracks.
+  pub fn new<I: Index>(index: &I, sym_table: &SymTable) -> ConjTable {
+    let group = sym_table.group();
+    assert_keeps_pieces(index, group, sym_table.move_set());
+    let mut conj = Vec::with_capacity(I::NUM_ELEMS as usize * group.len());
+    for i in 0..I::NUM_ELEMS {
+      let c = index.from_index(i);
+      for s in group.syms() {
+        conj.push(index.from_cube(&conjugate(&c, s)));
+      }
+    }
+    ConjTable {
+      num_syms: group.len(),
+      conj: conj.into_boxed_slice(),
+    }
+  }
+
+  /// Conjugate `index` by symmetry `s`.
+  pub fn conjugate(&self, index: u32, s: usize) -> u32 {
+    self.conj[index as usize * self.num_syms + s]
+  }
+
+  /// Get the number of indexes.
+  pub fn len(&self) -> usize {
+    self.conj.len() / self.num_syms
+  }
+
+  /// Test if there are no indexes.
+  pub fn is_empty(&self) -> bool {
+    self.conj.is_empty()
+  }
+}
+
+/// Generate a pruning table with an entry for each symmetry class of
+/// `sym_table`, giving the fewest moves needed to reach the class from
+/// `init_class`, like `gen_prune_table`. Look up a sym-coordinate with
+/// `table[class]`.
+///
+/// Panics if `init_class` isn't fixed by every symmetry, since the distances
+/// to it wouldn't be the same for every index in a class.
+pub fn gen_sym_prune_table(sym_table: &SymTable, init_class: u32) -> Box<[u8]> {
+  let num_syms = sym_table.group().len();
+  assert!(
+    (0..num_syms).all(|s| sym_table.fixes(init_class, s)),
+    "the initial index isn't fixed by every symmetry"
+  );
+  let move_set = sym_table.move_set();
+  let mut table = vec![u8::MAX; sym_table.num_classes()];
+  table[init_class as usize] = 0;
+  let mut frontier = vec![init_class];
+  let mut next = Vec::new();
+  let mut depth = 0;
+  while !frontier.is_empty() {
+    depth += 1;
+    assert!(depth < u8::MAX, "pruning table depth doesn't fit in a u8");
+    for &class in &frontier {
+      for i in 0..move_set.len() {
+        let mut coord = (class, 0);
+        for _ in 0..move_set.powers(i) {
+          coord = sym_table.transition(coord, i);
+          if table[coord.0 as usize] == u8::MAX {
+            table[coord.0 as usize] = depth;
+            next.push(coord.0);
+          }
+        }
+      }
+    }
+    std::mem::swap(&mut frontier, &mut next);
+    next.clear();
+  }
+  table.into_boxed_slice()
+}
+
+/// Generate a pruning table for the pairs of a sym-coordinate of `sym_table`
+/// and an index of `trans_table`, giving the fewest moves needed to reach
+/// each pair from `(init_class, init_index)`. The index is conjugated by the
+/// symmetry of the sym-coordinate, so look up a pair with
+/// `table[class * conj_table.len() + conj_table.conjugate(index, sym)]`.
+///
+/// Panics if the tables were generated for different move sets, or if the
+/// initial pair isn't fixed by every symmetry.
+pub fn gen_sym_product_prune_table(
+  sym_table: &SymTable,
+  trans_table: &TransitionTable,
+  conj_table: &ConjTable,
+  init_class: u32,
+  init_index: u32,
+) -> Box<[u8]> {
+  let move_set = trans_table.move_set();
+  assert_eq!(sym_table.move_set(), move_set);
+  let num_syms = sym_table.group().len();
+  assert!(
+    (0..num_syms).all(|s| sym_table.fixes(init_class, s)
+      && conj_table.conjugate(init_index, s) == init_index),
+    "the initial index isn't fixed by every symmetry"
+  );
+  let len = trans_table.len();
+  let mut table = vec![u8::MAX; sym_table.num_classes() * len];
+  // Each entry stands for the representative paired with the index. A
+  // symmetry that fixes the representative gives another entry for the same
+  // pair, so those are filled in together.
+  let mut visit = |class: u32, index: u32, depth: u8| {
+    let entry = class as usize * len + index as usize;
+    if table[entry] != u8::MAX {
+      return false;
+    }
+    for s in (0..num_syms).filter(|&s| sym_table.fixes(class, s)) {
+      let index = conj_table.conjugate(index, s);
+      table[class as usize * len + index as usize] = depth;
+    }
+    true
+  };
+  visit(init_class, init_index, 0);
+  let mut frontier = vec![(init_class, init_index)];
+  let mut next = Vec::new();
+  let mut depth = 0;
+  while !frontier.is_empty() {
+    depth += 1;
+    assert!(depth < u8::MAX, "pruning table depth doesn't fit in a u8");
+    for &(class, index) in &frontier {
+      for i in 0..move_set.len() {
+        let (mut coord, mut index) = ((class, 0), index);
+        for _ in 0..move_set.powers(i) {
+          coord = sym_table.transition(coord, i);
+          index = trans_table[index as usize][i];
+          let conj = conj_table.conjugate(index, coord.1 as usize);
+          if visit(coord.0, conj, depth) {
+            next.push((coord.0, conj));
+          }
+        }
+      }
+    }
+    std::mem::swap(&mut frontier, &mut next);
+    next.clear();
+  }
+  table.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::index::example::UL_CORNERS;
+  use crate::index::PieceSetIndex;
+  use crate::pruning::gen_prune_table;
+  use crate::transition::gen_transition_table;
+  use cube::parse_moves;
+
+  #[test]
+  fn groups() {
+    let rotations = SymmetryGroup::rotations();
+    let y_rotations = SymmetryGroup::y_rotations();
+    assert_eq!(24, rotations.len());
+    assert_eq!(4, y_rotations.len());
+    assert_eq!(8, SymmetryGroup::ud_rotations().len());
+    assert_eq!(2, y_rotations.inverse(2));
+    assert_eq!(3, y_rotations.inverse(1));
+    assert_eq!(3, y_rotations.product(1, 2));
+
+    assert!(rotations.preserves(&MoveSet::faces()));
+    assert!(!rotations.preserves(&"U R".parse().unwrap()));
+    assert!(y_rotations.preserves(&"U D M S".parse().unwrap()));
+    assert!(!y_rotations.preserves(&"U D M".parse().unwrap()));
+    let phase2 = "U D R2 L2 F2 B2".parse().unwrap();
+    assert!(SymmetryGroup::ud_rotations().preserves(&phase2));
+  }
+
+  #[test]
+  fn conjugation() {
+    let y = [Move::Rotation(Rotation::Y, 1)];
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R U").unwrap());
+    let mut expected = Cube::solved();
+    expected.do_moves(&parse_moves("F U").unwrap());
+    // y' R U y is F U.
+    assert!(same(&expected, &conjugate(&c, &y)));
+  }
+
+  #[test]
+  fn sym_reduced_pruning() {
+    let group = SymmetryGroup::y_rotations();
+    let move_set = MoveSet::faces();
+    let sym_table = SymTable::new(&UL_CORNERS, &group, &move_set);
+    let n = PieceSetIndex::<0, 4>::NUM_ELEMS;
+    assert!(sym_table.num_classes() * 4 >= n as usize);
+    assert!(sym_table.num_classes() * 3 < n as usize);
+
+    let table = gen_transition_table(&UL_CORNERS, &move_set);
+    let init = UL_CORNERS.from_cube(&Cube::solved());
+    let ptable = gen_prune_table(&table, init);
+    let init_class = sym_table.sym_coord(&UL_CORNERS, &Cube::solved()).0;
+    let sym_ptable = gen_sym_prune_table(&sym_table, init_class);
+    for i in (0..n).step_by(7) {
+      let c = UL_CORNERS.from_index(i);
+      let (class, s) = sym_table.sym_coord(&UL_CORNERS, &c);
+      let rep = sym_table.representative(class);
+      assert!(rep <= i);
+      let sym = &group.syms()[s as usize];
+      assert_eq!(rep, UL_CORNERS.from_cube(&conjugate(&c, sym)));
+      assert_eq!(ptable[i as usize], sym_ptable[class as usize]);
+
+      // The sym-coordinate follows the index through each move.
+      for m in 0..move_set.len() {
+        let (class, _) = sym_table.transition((class, s), m);
+        let next = UL_CORNERS.from_index(table[i as usize][m]);
+        assert_eq!(sym_table.sym_coord(&UL_CORNERS, &next).0, class);
+      }
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "pieces")]
+  fn other_pieces() {
+    // An x rotation takes the U layer corners to the F or B layer.
+    SymTable::new(&UL_CORNERS, &SymmetryGroup::rotations(), &MoveSet::faces());
+  }
+
+  #[test]
+  #[should_panic(expected = "fixed")]
+  fn unfixed_init() {
+    let group = SymmetryGroup::y_rotations();
+    let sym_table = SymTable::new(&UL_CORNERS, &group, &MoveSet::faces());
+    let mut c = Cube::solved();
+    c.do_moves(&parse_moves("R").unwrap());
+    gen_sym_prune_table(&sym_table, sym_table.sym_coord(&UL_CORNERS, &c).0);
+  }
+}
@@ -0,0 +1,411 @@
+//! Reading and writing transition and pruning tables, so they don't have to
+//! be generated every time a program starts.
+//!
+//! A table file starts with a header of the magic bytes `RXTB`, the format
+//! version, the kind of table and a fingerprint of the index the table was
+//! generated for. The table follows, and the file ends with a checksum of
+//! everything before it. Numbers are little endian.
+
+use crate::index::Index;
+use crate::move_set::MoveSet;
+use crate::pruning::PruneTable;
+use crate::transition::TransitionTable;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"RXTB";
+const VERSION: u16 = 1;
+const TRANSITION: u8 = 0;
+const PRUNE: u8 = 1;
+/// The longest move set a transition table can be stored with.
+const MAX_MOVE_SET_LEN: usize = 1024;
+
+/// The reason a table could not be loaded.
+#[derive(Debug)]
+pub enum TableError {
+  Io(io::Error),
+  /// The data is not a table file.
+  Magic,
+  /// The file was written by an unsupported version of the format.
+  Version(u16),
+  /// The file holds a different kind of table.
+  Kind(u8),
+  /// The file was generated for a different index.
+  Fingerprint,
+  /// The file is corrupt.
+  Checksum,
+  /// The table doesn't make sense, e.g. its move set can't be parsed.
+  Invalid,
+}
+
+impl std::fmt::Display for TableError {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      TableError::Io(e) => write!(f, "{}", e),
+      TableError::Magic => write!(f, "not a table file"),
+      TableError::Version(v) => write!(f, "unsupported table version {}", v),
+      TableError::Kind(k) => write!(f, "unexpected table kind {}", k),
+      TableError::Fingerprint => write!(f, "table is for a different index"),
+      TableError::Checksum => write!(f, "table checksum doesn't match"),
+      TableError::Invalid => write!(f, "invalid table"),
+    }
+  }
+}
+
+impl std::error::Error for TableError {}
+
+impl From<io::Error> for TableError {
+  fn from(e: io::Error) -> Self {
+    TableError::Io(e)
+  }
+}
+
+/// 64 bit FNV-1a.
+#[derive(Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+  fn new() -> Self {
+    Fnv(0xcbf2_9ce4_8422_2325)
+  }
+
+  fn update(&mut self, bytes: &[u8]) {
+    for &b in bytes {
+      self.0 = (self.0 ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+    }
+  }
+}
+
+/// Get a fingerprint of how `index` encodes cubes and how the generators of
+/// `move_set` change them. Changing either changes the fingerprint, so
+/// tables generated before the change are rejected.
+pub fn fingerprint<I: Index>(index: &I, move_set: &MoveSet) -> u64 {
+  let mut hash = Fnv::new();
+  hash.update(&I::NUM_ELEMS.to_le_bytes());
+  hash.update(move_set.to_string().as_bytes());
+  // Checking a sample of the indexes is enough to catch most changes.
+  let step = (I::NUM_ELEMS / 64).max(1);
+  for i in (0..I::NUM_ELEMS).step_by(step as usize) {
+    let c = index.from_index(i);
+    hash.update(&index.from_cube(&c).to_le_bytes());
+    for &m in move_set.moves() {
+      let mut c = c;
+      c.do_move(m);
+      hash.update(&index.from_cube(&c).to_le_bytes());
+    }
+  }
+  hash.0
+}
+
+/// Writes to a buffer while hashing what is written.
+struct Writer {
+  buf: Vec<u8>,
+}
+
+impl Writer {
+  fn new(kind: u8, fingerprint: u64) -> Self {
+    let mut w = Writer { buf: Vec::new() };
+    w.put(MAGIC);
+    w.put(&VERSION.to_le_bytes());
+    w.put(&[kind]);
+    w.put(&fingerprint.to_le_bytes());
+    w
+  }
+
+  fn put(&mut self, bytes: &[u8]) {
+    self.buf.extend_from_slice(bytes);
+  }
+
+  fn finish<W: Write>(mut self, w: &mut W) -> io::Result<()> {
+    let mut hash = Fnv::new();
+    hash.update(&self.buf);
+    self.put(&hash.0.to_le_bytes());
+    w.write_all(&self.buf)
+  }
+}
+
+/// Reads from `r` while hashing what is read.
+struct Reader<'a, R> {
+  r: &'a mut R,
+  hash: Fnv,
+}
+
+impl<'a, R: Read> Reader<'a, R> {
+  /// Read a header, checking that it is for a table of `kind` and
+  /// `fingerprint`.
+  fn new(r: &'a mut R, kind: u8, fingerprint: u64) -> Result<Self, TableError> {
+    let mut reader = Reader {
+      r,
+      hash: Fnv::new(),
+    };
+    if &reader.get::<4>()? != MAGIC {
+      return Err(TableError::Magic);
+    }
+    let version = u16::from_le_bytes(reader.get()?);
+    if version != VERSION {
+      return Err(TableError::Version(version));
+    }
+    let [k] = reader.get()?;
+    if k != kind {
+      return Err(TableError::Kind(k));
+    }
+    if u64::from_le_bytes(reader.get()?) != fingerprint {
+      return Err(TableError::Fingerprint);
+    }
+    Ok(reader)
+  }
+
+  fn get<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    self.fill(&mut bytes)?;
+    Ok(bytes)
+  }
+
+  fn fill(&mut self, bytes: &mut [u8]) -> io::Result<()> {
+    self.r.read_exact(bytes)?;
+    self.hash.update(bytes);
+    Ok(())
+  }
+
+  /// Read `len` bytes, failing instead of aborting if they can't be
+  /// allocated.
+  fn get_vec(&mut self, len: usize) -> Result<Vec<u8>, TableError> {
+    let mut bytes = Vec::new();
+    bytes
+      .try_reserve_exact(len)
+      .map_err(|_| TableError::Invalid)?;
+    bytes.resize(len, 0);
+    self.fill(&mut bytes)?;
+    Ok(bytes)
+  }
+
+  /// Read a length, checking that it is `expected` before anything is
+  /// allocated for it.
+  fn get_len(&mut self, expected: usize) -> Result<usize, TableError> {
+    let len = u64::from_le_bytes(self.get()?);
+    if len != expected as u64 {
+      return Err(TableError::Invalid);
+    }
+    Ok(expected)
+  }
+
+  /// Read the checksum and check it matches.
+  fn finish(self) -> Result<(), TableError> {
+    let hash = self.hash.0;
+    let mut bytes = [0; 8];
+    self.r.read_exact(&mut bytes)?;
+    if u64::from_le_bytes(bytes) != hash {
+      return Err(TableError::Checksum);
+    }
+    Ok(())
+  }
+}
+
+/// Write `table` to `w`. `fingerprint` should come from `fingerprint`.
+pub fn save_transition_table<W: Write>(
+  w: &mut W,
+  table: &TransitionTable,
+  fingerprint: u64,
+) -> io::Result<()> {
+  let mut writer = Writer::new(TRANSITION, fingerprint);
+  let moves: Vec<String> = table
+    .move_set()
+    .moves()
+    .iter()
+    .map(|m| m.to_string())
+    .collect();
+  let move_set = moves.join(" ");
+  writer.put(&(move_set.len() as u32).to_le_bytes());
+  writer.put(move_set.as_bytes());
+  writer.put(&(table.len() as u64).to_le_bytes());
+  for i in 0..table.len() {
+    for &next in &table[i] {
+      writer.put(&next.to_le_bytes());
+    }
+  }
+  writer.finish(w)
+}
+
+/// Read a table written by `save_transition_table`, checking that it was
+/// written with the same `fingerprint` and has `len` indexes, which is
+/// usually `I::NUM_ELEMS`.
+pub fn load_transition_table<R: Read>(
+  r: &mut R,
+  len: usize,
+  fingerprint: u64,
+) -> Result<TransitionTable, TableError> {
+  let mut reader = Reader::new(r, TRANSITION, fingerprint)?;
+  let move_set_len = u32::from_le_bytes(reader.get()?) as usize;
+  if move_set_len > MAX_MOVE_SET_LEN {
+    return Err(TableError::Invalid);
+  }
+  let move_set: MoveSet = String::from_utf8(reader.get_vec(move_set_len)?)
+    .ok()
+    .and_then(|s| s.parse().ok())
+    .ok_or(TableError::Invalid)?;
+  let len = reader.get_len(len)?;
+  let bytes = len
+    .checked_mul(move_set.len())
+    .and_then(|n| n.checked_mul(4))
+    .ok_or(TableError::Invalid)?;
+  let table: Vec<u32> = reader
+    .get_vec(bytes)?
+    .chunks_exact(4)
+    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    .collect();
+  reader.finish()?;
+  if table.iter().any(|&i| i as usize >= len) {
+    return Err(TableError::Invalid);
+  }
+  Ok(TransitionTable::from_parts(
+    table.into_boxed_slice(),
+    move_set,
+  ))
+}
+
+/// Write the pruning table `table` to `w`. `fingerprint` should come from
+/// `fingerprint`, combined with anything else the table depends on such as
+/// the initial index.
+pub fn save_prune_table<W: Write, P: PruneTable>(
+  w: &mut W,
+  table: &P,
+  fingerprint: u64,
+) -> io::Result<()> {
+  let bytes = table.as_bytes();
+  let mut writer = Writer::new(PRUNE, fingerprint);
+  writer.put(&(bytes.len() as u64).to_le_bytes());
+  writer.put(bytes);
+  writer.finish(w)
+}
+
+/// Read a table written by `save_prune_table`, checking that it was written
+/// with the same `fingerprint` and is stored as `P` for `len` indexes.
+/// `init_index` is the index the table was generated from.
+pub fn load_prune_table<P: PruneTable, R: Read>(
+  r: &mut R,
+  len: usize,
+  init_index: u32,
+  fingerprint: u64,
+) -> Result<P, TableError> {
+  let mut reader = Reader::new(r, PRUNE, fingerprint)?;
+  let len = reader.get_len(P::num_bytes(len))?;
+  let table = reader.get_vec(len)?;
+  reader.finish()?;
+  Ok(P::from_bytes(table.into_boxed_slice(), init_index))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::index::example::UF;
+  use crate::pruning::{gen_prune_table, Mod3Table};
+  use crate::transition::gen_transition_table;
+
+  #[test]
+  fn round_trip() {
+    let move_set = "U D F B R L M".parse().unwrap();
+    let fp = fingerprint(&UF, &move_set);
+    let table = gen_transition_table(&UF, &move_set);
+    let ptable = gen_prune_table(&table, 0);
+
+    let mut buf = Vec::new();
+    save_transition_table(&mut buf, &table, fp).unwrap();
+    save_prune_table(&mut buf, &ptable, fp).unwrap();
+
+    let mut r = &buf[..];
+    let loaded = load_transition_table(&mut r, table.len(), fp).unwrap();
+    assert_eq!(&move_set, loaded.move_set());
+    assert_eq!(table.len(), loaded.len());
+    assert!((0..table.len()).all(|i| table[i] == loaded[i]));
+    let loaded: Box<[u8]> =
+      load_prune_table(&mut r, table.len(), 0, fp).unwrap();
+    assert_eq!(ptable, loaded);
+    assert!(r.is_empty());
+  }
+
+  #[test]
+  fn compact_round_trip() {
+    let move_set = "U D F B R L M".parse().unwrap();
+    let fp = fingerprint(&UF, &move_set);
+    let table = gen_transition_table(&UF, &move_set);
+    let mod3 = Mod3Table::generate(&table, 0);
+    let mut buf = Vec::new();
+    save_prune_table(&mut buf, &mod3, fp).unwrap();
+
+    let loaded: Mod3Table =
+      load_prune_table(&mut &buf[..], table.len(), 0, fp).unwrap();
+    assert_eq!(mod3.as_bytes(), loaded.as_bytes());
+    assert!((0..table.len() as u32)
+      .all(|i| mod3.distance(&table, i) == loaded.distance(&table, i)));
+    // The same bytes can't be read as a table stored in a different way.
+    assert!(matches!(
+      load_prune_table::<Box<[u8]>, _>(&mut &buf[..], table.len(), 0, fp),
+      Err(TableError::Invalid)
+    ));
+  }
+
+  #[test]
+  fn errors() {
+    let move_set = "R U".parse().unwrap();
+    let fp = fingerprint(&UF, &move_set);
+    assert_ne!(fp, fingerprint(&UF, &"R U M".parse().unwrap()));
+    let table = gen_transition_table(&UF, &move_set);
+    let mut buf = Vec::new();
+    save_transition_table(&mut buf, &table, fp).unwrap();
+
+    let len = table.len();
+    let load = |buf: &[u8], fp| load_transition_table(&mut &buf[..], len, fp);
+    assert!(matches!(load(&buf, fp + 1), Err(TableError::Fingerprint)));
+    assert!(matches!(
+      load_transition_table(&mut &buf[..], len - 1, fp),
+      Err(TableError::Invalid)
+    ));
+    assert!(matches!(
+      load_prune_table::<Box<[u8]>, _>(&mut &buf[..], len, 0, fp),
+      Err(TableError::Kind(TRANSITION))
+    ));
+    assert!(matches!(load(&buf[1..], fp), Err(TableError::Magic)));
+    assert!(matches!(load(&buf[..20], fp), Err(TableError::Io(_))));
+
+    let mut corrupt = buf.clone();
+    corrupt[30] ^= 1;
+    assert!(matches!(load(&corrupt, fp), Err(TableError::Checksum)));
+
+    let mut old = buf;
+    old[4] = 0;
+    assert!(matches!(load(&old, fp), Err(TableError::Version(0))));
+  }
+
+  #[test]
+  fn corrupt_lengths() {
+    let move_set = "R U".parse().unwrap();
+    let fp = fingerprint(&UF, &move_set);
+    let table = gen_transition_table(&UF, &move_set);
+    let ptable = gen_prune_table(&table, 0);
+    let (len, header) = (table.len(), MAGIC.len() + 2 + 1 + 8);
+
+    // Huge lengths are rejected before anything is allocated for them.
+    let mut buf = Vec::new();
+    save_transition_table(&mut buf, &table, fp).unwrap();
+    let mut corrupt = buf.clone();
+    corrupt[header..header + 4].copy_from_slice(&[0xff; 4]);
+    assert!(matches!(
+      load_transition_table(&mut &corrupt[..], len, fp),
+      Err(TableError::Invalid)
+    ));
+    let table_len = header + 4 + "R U".len();
+    let mut corrupt = buf;
+    corrupt[table_len..table_len + 8].copy_from_slice(&[0xff; 8]);
+    assert!(matches!(
+      load_transition_table(&mut &corrupt[..], len, fp),
+      Err(TableError::Invalid)
+    ));
+
+    let mut buf = Vec::new();
+    save_prune_table(&mut buf, &ptable, fp).unwrap();
+    buf[header..header + 8].copy_from_slice(&[0xff; 8]);
+    assert!(matches!(
+      load_prune_table::<Box<[u8]>, _>(&mut &buf[..], len, 0, fp),
+      Err(TableError::Invalid)
+    ));
+  }
+}
@@ -1,47 +1,104 @@
 use crate::index::Index;
-use cube::Move::*;
-use cube::{Face, Slice};
+use crate::move_set::MoveSet;
 
-/// Generate a transition table for the `Index` `I`.
-pub fn gen_transition_table<I: Index>(index: &I) -> Box<[[u32; 7]]> {
-  let mut res = Vec::<[u32; 7]>::with_capacity(I::NUM_ELEMS as usize);
+/// A table of the index reached by doing each generator of a `MoveSet` from
+/// each index. `table[i][m]` is the index reached by doing generator `m` from
+/// index `i`.
+pub struct TransitionTable {
+  table: Box<[u32]>,
+  move_set: MoveSet,
+}
 
-  for n in 0..I::NUM_ELEMS {
-    res.push(Default::default());
+impl TransitionTable {
+  /// Create a table from its rows, laid out one after another.
+  pub(crate) fn from_parts(table: Box<[u32]>, move_set: MoveSet) -> Self {
+    TransitionTable { table, move_set }
+  }
+
+  /// Get the `MoveSet` that the table was generated for.
+  pub fn move_set(&self) -> &MoveSet {
+    &self.move_set
+  }
+
+  /// Get the number of indexes in the table.
+  pub fn len(&self) -> usize {
+    self.table.len() / self.move_set.len()
+  }
+
+  /// Test if the table has no indexes.
+  pub fn is_empty(&self) -> bool {
+    self.table.is_empty()
+  }
+}
+
+impl std::ops::Index<usize> for TransitionTable {
+  type Output = [u32];
+
+  fn index(&self, i: usize) -> &[u32] {
+    let n = self.move_set.len();
+    &self.table[i * n..(i + 1) * n]
+  }
+}
 
+/// Generate a transition table for the `Index` `I` and the generators of
+/// `move_set`.
+pub fn gen_transition_table<I: Index>(
+  index: &I,
+  move_set: &MoveSet,
+) -> TransitionTable {
+  let mut res =
+    Vec::<u32>::with_capacity(I::NUM_ELEMS as usize * move_set.len());
+
+  for n in 0..I::NUM_ELEMS {
     let c = index.from_index(n);
 
-    for (i, &f) in [
-      Face(Face::U, 1),
-      Face(Face::D, 1),
-      Face(Face::F, 1),
-      Face(Face::B, 1),
-      Face(Face::R, 1),
-      Face(Face::L, 1),
-      Slice(Slice::M, 1),
-    ]
-    .iter()
-    .enumerate()
-    {
+    for &m in move_set.moves() {
       let mut c2 = c;
-      c2.do_move(f);
-      let n2 = index.from_cube(&c2);
-      res[n as usize][i] = n2;
+      c2.do_move(m);
+      res.push(index.from_cube(&c2));
+    }
+  }
+  TransitionTable {
+    table: res.into_boxed_slice(),
+    move_set: move_set.clone(),
+  }
+}
+
+/// Combine the tables of two indexes into a table for the pair of them, so
+/// that a pruning table can be generated for both at once. The index of the
+/// pair `(i, j)` is `i * b.len() + j`.
+///
+/// Panics if the tables were generated for different move sets.
+pub fn gen_product_table(
+  a: &TransitionTable,
+  b: &TransitionTable,
+) -> TransitionTable {
+  assert_eq!(a.move_set(), b.move_set());
+  let mut res = Vec::<u32>::with_capacity(a.table.len() * b.len());
+  for i in 0..a.len() {
+    for j in 0..b.len() {
+      for (&x, &y) in a[i].iter().zip(&b[j]) {
+        res.push(x * b.len() as u32 + y);
+      }
     }
   }
-  res.into_boxed_slice()
+  TransitionTable {
+    table: res.into_boxed_slice(),
+    move_set: a.move_set().clone(),
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use cube::Cube;
+  use cube::{Cube, Face, Move::*, Slice};
 
   #[test]
   fn minimal_uf() {
     use crate::index::example::UF;
     let uf = UF;
-    let table = gen_transition_table(&uf);
+    let table = gen_transition_table(&uf, &"U D F B R L M".parse().unwrap());
+    assert_eq!(24, table.len());
 
     let mut c = Cube::solved();
     let solved_index = uf.from_cube(&c);
@@ -56,4 +113,39 @@ mod tests {
     assert_eq!(f_index, table[solved_index as usize][2]);
     assert_eq!(f_index, table[f_index as usize][0]);
   }
+
+  #[test]
+  fn move_set() {
+    use crate::index::example::UF;
+    let uf = UF;
+    let table = gen_transition_table(&uf, &"M U2".parse().unwrap());
+    assert_eq!(2, table[0].len());
+
+    let mut c = Cube::solved();
+    let solved_index = uf.from_cube(&c);
+    c.do_move(Slice(Slice::M, 1));
+    assert_eq!(uf.from_cube(&c), table[solved_index as usize][0]);
+    c.do_move(Face(Face::U, 2));
+    let m_index = table[solved_index as usize][0];
+    assert_eq!(uf.from_cube(&c), table[m_index as usize][1]);
+  }
+
+  #[test]
+  fn product() {
+    use crate::index::example::{UF, UL};
+    let move_set = "U D F B R L M".parse().unwrap();
+    let uf = gen_transition_table(&UF, &move_set);
+    let ul = gen_transition_table(&UL, &move_set);
+    let table = gen_product_table(&uf, &ul);
+    assert_eq!(24 * 24, table.len());
+
+    let mut c = Cube::solved();
+    c.do_moves(&cube::parse_moves("R U' M2").unwrap());
+    let i = UF.from_cube(&c) * 24 + UL.from_cube(&c);
+    c.do_move(Face(Face::F, 1));
+    assert_eq!(
+      UF.from_cube(&c) * 24 + UL.from_cube(&c),
+      table[i as usize][2]
+    );
+  }
 }
@@ -0,0 +1,227 @@
+//! Coordinates of a `CubieCube`, and `Index`es for them.
+//!
+//! Phase 1 uses the corner orientation (twist), the edge orientation (flip)
+//! and which positions the E slice edges are in (slice). Phase 2 uses the
+//! permutations of the corners, the U and D layer edges and the E slice
+//! edges, which are only meaningful once phase 1 is solved.
+
+use cube::{Cube, CubieCube};
+use solver::index::Index;
+
+/// Get the number of ways of choosing `k` items from `n`.
+fn choose(n: u32, k: u32) -> u32 {
+  if k > n {
+    return 0;
+  }
+  (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+}
+
+/// Get the rank of the permutation `p` in lexicographic order.
+fn perm_rank(p: &[u8]) -> u32 {
+  let mut rank = 0;
+  for i in 0..p.len() {
+    let smaller = p[i + 1..].iter().filter(|&&x| x < p[i]).count();
+    rank = rank * (p.len() - i) as u32 + smaller as u32;
+  }
+  rank
+}
+
+/// Fill `p` with the permutation of `0..p.len()` with rank `rank`.
+fn perm_unrank(p: &mut [u8], mut rank: u32) {
+  let n = p.len();
+  let mut digits = vec![0; n];
+  for i in (0..n).rev() {
+    let base = (n - i) as u32;
+    digits[i] = (rank % base) as usize;
+    rank /= base;
+  }
+  let mut unused: Vec<u8> = (0..n as u8).collect();
+  for (x, d) in p.iter_mut().zip(digits) {
+    *x = unused.remove(d);
+  }
+}
+
+/// The first of the E slice edges, FR, FL, BL and BR.
+const SLICE_EDGE: u8 = 8;
+
+pub fn twist(cc: &CubieCube) -> u32 {
+  cc.co[..7].iter().fold(0, |i, &o| i * 3 + o as u32)
+}
+
+/// Set the corner orientations, twisting the last corner so the total twist
+/// is 0.
+pub fn set_twist(cc: &mut CubieCube, mut i: u32) {
+  let mut total = 0;
+  for o in cc.co[..7].iter_mut().rev() {
+    *o = (i % 3) as u8;
+    total += *o;
+    i /= 3;
+  }
+  cc.co[7] = (3 - total % 3) % 3;
+}
+
+pub fn flip(cc: &CubieCube) -> u32 {
+  cc.eo[..11].iter().fold(0, |i, &o| i * 2 + o as u32)
+}
+
+/// Set the edge orientations, flipping the last edge so an even number of
+/// edges are flipped.
+pub fn set_flip(cc: &mut CubieCube, mut i: u32) {
+  let mut total = 0;
+  for o in cc.eo[..11].iter_mut().rev() {
+    *o = (i % 2) as u8;
+    total += *o;
+    i /= 2;
+  }
+  cc.eo[11] = total % 2;
+}
+
+pub fn slice(cc: &CubieCube) -> u32 {
+  let mut i = 0;
+  let mut k = 0;
+  for (pos, &e) in cc.ep.iter().enumerate() {
+    if e >= SLICE_EDGE {
+      k += 1;
+      i += choose(pos as u32, k);
+    }
+  }
+  i
+}
+
+/// Put the E slice edges in the positions given by `i`, and the other edges
+/// in the rest, each in order.
+pub fn set_slice(cc: &mut CubieCube, mut i: u32) {
+  let mut slice = [false; 12];
+  for k in (1..=4).rev() {
+    let pos = (0..12).rev().find(|&p| choose(p, k) <= i).unwrap();
+    i -= choose(pos, k);
+    slice[pos as usize] = true;
+  }
+  let (mut other, mut edge) = (0, SLICE_EDGE);
+  for (e, &s) in cc.ep.iter_mut().zip(&slice) {
+    if s {
+      *e = edge;
+      edge += 1;
+    } else {
+      *e = other;
+      other += 1;
+    }
+  }
+}
+
+pub fn corner_perm(cc: &CubieCube) -> u32 {
+  perm_rank(&cc.cp)
+}
+
+pub fn set_corner_perm(cc: &mut CubieCube, i: u32) {
+  perm_unrank(&mut cc.cp, i);
+}
+
+/// The permutation of the U and D layer edges, which must be in those
+/// layers.
+pub fn ud_edge_perm(cc: &CubieCube) -> u32 {
+  perm_rank(&cc.ep[..8])
+}
+
+pub fn set_ud_edge_perm(cc: &mut CubieCube, i: u32) {
+  perm_unrank(&mut cc.ep[..8], i);
+}
+
+/// The permutation of the E slice edges, which must be in the E slice.
+pub fn slice_perm(cc: &CubieCube) -> u32 {
+  perm_rank(&cc.ep[8..])
+}
+
+pub fn set_slice_perm(cc: &mut CubieCube, i: u32) {
+  perm_unrank(&mut cc.ep[8..], i);
+  for e in &mut cc.ep[8..] {
+    *e += SLICE_EDGE;
+  }
+}
+
+/// Define an `Index` for a coordinate, which decodes to a solved cube with
+/// the coordinate set.
+macro_rules! coord_index {
+  ($(#[$doc: meta])* $name: ident, $n: expr, $get: ident, $set: ident) => {
+    $(#[$doc])*
+    pub struct $name;
+
+    impl Index for $name {
+      const NUM_ELEMS: u32 = $n;
+
+      fn from_cube(&self, c: &Cube) -> u32 {
+        $get(&CubieCube::from_cube(c).expect("invalid pieces"))
+      }
+
+      fn from_index(&self, i: u32) -> Cube {
+        let mut cc = CubieCube::solved();
+        $set(&mut cc, i);
+        cc.to_cube()
+      }
+    }
+  };
+}
+
+coord_index!(
+  /// The orientation of the corners.
+  Twist, 2187, twist, set_twist
+);
+coord_index!(
+  /// The orientation of the edges.
+  Flip, 2048, flip, set_flip
+);
+coord_index!(
+  /// The positions of the E slice edges, ignoring their order.
+  Slice, 495, slice, set_slice
+);
+coord_index!(
+  /// The permutation of the corners.
+  CornerPerm, 40320, corner_perm, set_corner_perm
+);
+coord_index!(
+  /// The permutation of the U and D layer edges, in phase 2.
+  UDEdgePerm, 40320, ud_edge_perm, set_ud_edge_perm
+);
+coord_index!(
+  /// The permutation of the E slice edges, in phase 2.
+  SlicePerm, 24, slice_perm, set_slice_perm
+);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cube::parse_moves;
+  use solver::index::exhaustive_index_check;
+
+  #[test]
+  fn exhaustive() {
+    exhaustive_index_check(&Twist);
+    exhaustive_index_check(&Flip);
+    exhaustive_index_check(&Slice);
+    exhaustive_index_check(&CornerPerm);
+    exhaustive_index_check(&UDEdgePerm);
+    exhaustive_index_check(&SlicePerm);
+  }
+
+  #[test]
+  fn coordinates() {
+    let solved = CubieCube::solved();
+    assert_eq!(0, twist(&solved));
+    assert_eq!(0, flip(&solved));
+    assert_eq!(0, corner_perm(&solved));
+    assert_eq!(494, slice(&solved));
+
+    // Phase 1 moves keep the slice edges in the slice, and don't twist or
+    // flip anything.
+    let mut cc = solved;
+    cc.do_moves(&parse_moves("U R2 D' F2 L2 B2 U2").unwrap());
+    assert_eq!((0, 0, 494), (twist(&cc), flip(&cc), slice(&cc)));
+    assert_ne!(0, corner_perm(&cc));
+
+    let mut cc = solved;
+    cc.do_moves(&parse_moves("F").unwrap());
+    assert_ne!(0, twist(&cc));
+    assert_ne!(0, flip(&cc));
+    assert_ne!(494, slice(&cc));
+  }
+}
@@ -0,0 +1,231 @@
+//! A two-phase solver for the whole cube, as described by Herbert Kociemba.
+//!
+//! Phase 1 brings the cube into the group `<U, D, R2, L2, F2, B2>`, and phase
+//! 2 solves it using only those moves. Each phase on its own is short, so a
+//! solution is found quickly. The search then keeps trying longer phase 1
+//! solutions, which can give shorter solutions overall, until the solution
+//! is short enough or the time runs out.
+
+use cube::{Alg, Cube, CubeError, CubieCube, Face, Move};
+use solver::iddfs::iddfs_each;
+use solver::solve::{SolveOptions, Solver, StopReason};
+use std::time::{Duration, Instant};
+
+pub mod coords;
+pub mod phase1;
+pub mod phase2;
+
+use phase1::Phase1Info;
+use phase2::Phase2Info;
+
+/// Every state can be brought into the phase 2 group in this many moves.
+const MAX_PHASE1: usize = 12;
+/// Every state in the phase 2 group can be solved in this many moves.
+const MAX_PHASE2: usize = 18;
+
+/// Test if `m` is in the phase 2 group `<U, D, R2, L2, F2, B2>`.
+fn in_phase2(m: &Move) -> bool {
+  match m {
+    Move::Face(Face::U, _) | Move::Face(Face::D, _) => true,
+    m => m.amount() == 2,
+  }
+}
+
+/// When `TwoPhase::solve` stops looking for shorter solutions.
+#[derive(Clone, Debug)]
+pub struct TwoPhaseOptions {
+  /// Stop as soon as a solution of at most this many moves is found. With 0,
+  /// the search only stops when the solution is known to be optimal.
+  pub target_length: usize,
+  /// The most time to spend looking for a shorter solution, after the first
+  /// one is found. It is checked after each phase 1 solution and during
+  /// each phase 2 search. `Instant` is only used when this is set, since it
+  /// panics on wasm.
+  pub timeout: Option<Duration>,
+}
+
+impl Default for TwoPhaseOptions {
+  fn default() -> Self {
+    TwoPhaseOptions {
+      target_length: 20,
+      timeout: Some(Duration::from_secs(1)),
+    }
+  }
+}
+
+/// The tables for both phases.
+pub struct TwoPhase {
+  phase1: Phase1Info,
+  phase2: Phase2Info,
+}
+
+impl TwoPhase {
+  pub fn new() -> Self {
+    TwoPhase {
+      phase1: Phase1Info::new(),
+      phase2: Phase2Info::new(),
+    }
+  }
+
+  /// Find a short solution for `c`, which may be in any orientation. The
+  /// moves are face turns, named by the faces of the cube as it is held.
+  pub fn solve(
+    &self,
+    c: &Cube,
+    options: &TwoPhaseOptions,
+  ) -> Result<Vec<Move>, CubeError> {
+    c.validate()?;
+    let cc = CubieCube::from_cube(c).unwrap();
+    // The time runs from when the first solution is found.
+    let mut deadline: Option<Instant> = None;
+    let timed_out =
+      |deadline: Option<Instant>| deadline.is_some_and(|d| Instant::now() >= d);
+
+    let state = Phase1Info::state(&cc);
+    let mut best: Option<Vec<Move>> = None;
+    // The shortest solution can have a phase 1 longer than `MAX_PHASE1`, so
+    // only the length of the best solution so far limits the depth.
+    for depth in 0.. {
+      // Every solution with a longer phase 1 is also longer overall.
+      if best.as_ref().is_some_and(|b| depth >= b.len()) {
+        break;
+      }
+      debug_assert!(depth <= MAX_PHASE1 || best.is_some());
+      let stopped = iddfs_each(state, &self.phase1, depth, |phase1| {
+        // If the last move is in the phase 2 group, then the moves before it
+        // were already tried as a shorter phase 1.
+        if phase1.last().is_some_and(in_phase2) {
+          return timed_out(deadline);
+        }
+        let max_depth = best
+          .as_ref()
+          .map_or(MAX_PHASE2, |b| b.len().saturating_sub(depth + 1));
+        let mut cc = cc;
+        cc.do_moves(phase1);
+        let phase2_options = SolveOptions {
+          max_depth,
+          timeout: deadline
+            .map(|d| d.saturating_duration_since(Instant::now())),
+          ..Default::default()
+        };
+        let result = Solver::new(&self.phase2, phase2_options)
+          .solve(self.phase2.state(&cc));
+        if let Some(phase2) = result.solution {
+          let solution = Alg([phase1, &phase2].concat()).cancel().0;
+          if best.is_none() {
+            deadline = options.timeout.map(|t| Instant::now() + t);
+          }
+          if best.as_ref().is_none_or(|b| solution.len() < b.len()) {
+            best = Some(solution);
+          }
+        }
+        result.stop == StopReason::Timeout
+          || best.as_ref().is_some_and(|b| {
+            b.len() <= options.target_length || timed_out(deadline)
+          })
+      });
+      if stopped {
+        break;
+      }
+    }
+    Ok(best.unwrap())
+  }
+}
+
+impl Default for TwoPhase {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::OnceLock;
+
+  fn two_phase() -> &'static TwoPhase {
+    static TWO_PHASE: OnceLock<TwoPhase> = OnceLock::new();
+    TWO_PHASE.get_or_init(TwoPhase::new)
+  }
+
+  fn check(c: &Cube, options: &TwoPhaseOptions) -> Vec<Move> {
+    let solution = two_phase().solve(c, options).unwrap();
+    let mut c = *c;
+    c.do_moves(&solution);
+    assert_eq!(CubieCube::solved(), CubieCube::from_cube(&c).unwrap());
+    solution
+  }
+
+  #[test]
+  fn first_solution() {
+    let options = TwoPhaseOptions {
+      target_length: 30,
+      timeout: None,
+    };
+    for scramble in &[
+      "",
+      "R",
+      "D2 F2 U' B2 R2 U F2 L2 U' R' B' L F' D L' U' F U2 R B",
+      "L2 U B2 D' R2 U2 F2 L2 D R' F D' B L' U2 F' R D2 L B' U'",
+      "x y' R U F' M2 E S' r",
+    ] {
+      let solution = check(&Cube::from_moves(scramble).unwrap(), &options);
+      assert!(solution.len() <= 30);
+    }
+  }
+
+  #[test]
+  fn optimal() {
+    let options = TwoPhaseOptions {
+      target_length: 0,
+      timeout: None,
+    };
+    assert!(check(&Cube::from_moves("").unwrap(), &options).is_empty());
+    assert_eq!(1, check(&Cube::from_moves("F'").unwrap(), &options).len());
+    assert_eq!(
+      6,
+      check(&Cube::from_moves("R U F' L2 D B'").unwrap(), &options).len()
+    );
+  }
+
+  #[test]
+  fn long_phase1() {
+    // Every 14 move solution of this needs more than 12 moves to bring it
+    // into the phase 2 group.
+    let options = TwoPhaseOptions {
+      target_length: 14,
+      timeout: None,
+    };
+    let c = Cube::from_moves("F B2 R2 D F R2 B2 L2 U' D' F' L' D2 U'").unwrap();
+    let solution = check(&c, &options);
+    assert_eq!(14, solution.len());
+    let phase1 = solution.iter().rposition(|m| !in_phase2(m)).unwrap() + 1;
+    assert!(phase1 > 12);
+  }
+
+  #[test]
+  fn timeout() {
+    // An optimal solution would take far too long to find, so this only
+    // finishes if the search stops after the first solution.
+    let options = TwoPhaseOptions {
+      target_length: 0,
+      timeout: Some(Duration::ZERO),
+    };
+    let c =
+      Cube::from_moves("D2 F2 U' B2 R2 U F2 L2 U' R' B' L F' D L' U' F U2 R B")
+        .unwrap();
+    let solution = check(&c, &options);
+    assert!(solution.len() <= MAX_PHASE1 + MAX_PHASE2);
+  }
+
+  #[test]
+  fn invalid() {
+    let mut c = Cube::solved();
+    c.edges.swap(0, 1);
+    let options = TwoPhaseOptions::default();
+    assert_eq!(
+      Err(CubeError::FlippedEdges),
+      two_phase().solve(&c, &options)
+    );
+  }
+}
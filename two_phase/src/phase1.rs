@@ -0,0 +1,92 @@
+use crate::coords::{flip, slice, twist, Flip, Slice, Twist};
+use cube::CubieCube;
+use solver::heuristic::Combine;
+use solver::iddfs::IDDFSInfo;
+use solver::index::Index;
+use solver::move_set::MoveSet;
+use solver::pruning::gen_prune_table;
+use solver::transition::{
+  gen_product_table, gen_transition_table, TransitionTable,
+};
+
+/// IDDFS Info for phase 1, which orients the corners and edges and puts the
+/// E slice edges in the E slice, using `<U, D, F, B, R, L>`.
+pub struct Phase1Info {
+  twist: TransitionTable,
+  flip: TransitionTable,
+  slice: TransitionTable,
+  /// Pruning tables for the pairs (twist, slice) and (flip, slice).
+  twist_slice: Box<[u8]>,
+  flip_slice: Box<[u8]>,
+  solved: <Self as IDDFSInfo>::State,
+}
+
+impl IDDFSInfo for Phase1Info {
+  type State = (u32, u32, u32);
+
+  fn move_set(&self) -> &MoveSet {
+    self.twist.move_set()
+  }
+
+  fn is_solved(&self, state: &Self::State) -> bool {
+    *state == self.solved
+  }
+
+  fn transition(&self, state: &Self::State, m: usize) -> Self::State {
+    (
+      self.twist[state.0 as usize][m],
+      self.flip[state.1 as usize][m],
+      self.slice[state.2 as usize][m],
+    )
+  }
+
+  fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
+    depth_remaining < self.estimate(state) as usize
+  }
+}
+
+impl Phase1Info {
+  pub fn new() -> Self {
+    let move_set = MoveSet::faces();
+    let twist = gen_transition_table(&Twist, &move_set);
+    let flip = gen_transition_table(&Flip, &move_set);
+    let slice = gen_transition_table(&Slice, &move_set);
+    let solved = Phase1Info::state(&CubieCube::solved());
+    let twist_slice = gen_prune_table(
+      &gen_product_table(&twist, &slice),
+      solved.0 * Slice::NUM_ELEMS + solved.2,
+    );
+    let flip_slice = gen_prune_table(
+      &gen_product_table(&flip, &slice),
+      solved.1 * Slice::NUM_ELEMS + solved.2,
+    );
+    Phase1Info {
+      twist,
+      flip,
+      slice,
+      twist_slice,
+      flip_slice,
+      solved,
+    }
+  }
+
+  /// Get a lower bound on the moves needed to solve `state`.
+  pub fn estimate(&self, state: &<Self as IDDFSInfo>::State) -> u8 {
+    let twist_slice = state.0 * Slice::NUM_ELEMS + state.2;
+    let flip_slice = state.1 * Slice::NUM_ELEMS + state.2;
+    Combine::Max.combine([
+      self.twist_slice[twist_slice as usize],
+      self.flip_slice[flip_slice as usize],
+    ])
+  }
+
+  pub fn state(cc: &CubieCube) -> <Self as IDDFSInfo>::State {
+    (twist(cc), flip(cc), slice(cc))
+  }
+}
+
+impl Default for Phase1Info {
+  fn default() -> Self {
+    Self::new()
+  }
+}
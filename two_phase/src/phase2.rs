@@ -0,0 +1,174 @@
+use crate::coords::{
+  slice_perm, ud_edge_perm, CornerPerm, SlicePerm, UDEdgePerm,
+};
+use cube::CubieCube;
+use solver::heuristic::Combine;
+use solver::iddfs::IDDFSInfo;
+use solver::index::Index;
+use solver::move_set::MoveSet;
+use solver::pruning::gen_prune_table;
+use solver::symmetry::{
+  gen_sym_product_prune_table, ConjTable, SymCoord, SymTable, SymmetryGroup,
+};
+use solver::transition::{
+  gen_product_table, gen_transition_table, TransitionTable,
+};
+
+/// IDDFS Info for phase 2, which solves a cube that phase 1 has solved,
+/// using `<U, D, R2, L2, F2, B2>`.
+///
+/// The corner permutation is reduced by the rotations that keep the U-D
+/// axis, which keep the move set, so its tables have an entry for each
+/// symmetry class rather than each permutation.
+pub struct Phase2Info {
+  corners: SymTable,
+  edges: TransitionTable,
+  slice: TransitionTable,
+  /// Conjugates the slice permutation by the symmetry of the corners.
+  slice_conj: ConjTable,
+  /// Pruning tables for the pairs (corners, slice) and (edges, slice).
+  corners_slice: Box<[u8]>,
+  edges_slice: Box<[u8]>,
+  solved: <Self as IDDFSInfo>::State,
+}
+
+impl IDDFSInfo for Phase2Info {
+  type State = (SymCoord, u32, u32);
+
+  fn move_set(&self) -> &MoveSet {
+    self.edges.move_set()
+  }
+
+  fn is_solved(&self, state: &Self::State) -> bool {
+    // Every symmetry fixes the solved corners, so only the class matters.
+    state.0 .0 == self.solved.0 .0
+      && state.1 == self.solved.1
+      && state.2 == self.solved.2
+  }
+
+  fn transition(&self, state: &Self::State, m: usize) -> Self::State {
+    (
+      self.corners.transition(state.0, m),
+      self.edges[state.1 as usize][m],
+      self.slice[state.2 as usize][m],
+    )
+  }
+
+  fn prune(&self, state: &Self::State, depth_remaining: usize) -> bool {
+    depth_remaining < self.estimate(state) as usize
+  }
+}
+
+impl Phase2Info {
+  pub fn new() -> Self {
+    let move_set = "U D R2 L2 F2 B2".parse().unwrap();
+    let group = SymmetryGroup::ud_rotations();
+    let corners = SymTable::new(&CornerPerm, &group, &move_set);
+    let edges = gen_transition_table(&UDEdgePerm, &move_set);
+    let slice = gen_transition_table(&SlicePerm, &move_set);
+    let slice_conj = ConjTable::new(&SlicePerm, &corners);
+    let solved_cc = CubieCube::solved();
+    let solved = (
+      corners.sym_coord(&CornerPerm, &solved_cc.to_cube()),
+      ud_edge_perm(&solved_cc),
+      slice_perm(&solved_cc),
+    );
+    let corners_slice = gen_sym_product_prune_table(
+      &corners,
+      &slice,
+      &slice_conj,
+      solved.0 .0,
+      solved.2,
+    );
+    let edges_slice = gen_prune_table(
+      &gen_product_table(&edges, &slice),
+      solved.1 * SlicePerm::NUM_ELEMS + solved.2,
+    );
+    Phase2Info {
+      corners,
+      edges,
+      slice,
+      slice_conj,
+      corners_slice,
+      edges_slice,
+      solved,
+    }
+  }
+
+  /// Get a lower bound on the moves needed to solve `state`.
+  pub fn estimate(&self, state: &<Self as IDDFSInfo>::State) -> u8 {
+    let ((class, sym), edges, slice) = *state;
+    let corners_slice = class * SlicePerm::NUM_ELEMS
+      + self.slice_conj.conjugate(slice, sym as usize);
+    let edges_slice = edges * SlicePerm::NUM_ELEMS + slice;
+    Combine::Max.combine([
+      self.corners_slice[corners_slice as usize],
+      self.edges_slice[edges_slice as usize],
+    ])
+  }
+
+  /// Get the state of `cc`, which phase 1 must have solved.
+  pub fn state(&self, cc: &CubieCube) -> <Self as IDDFSInfo>::State {
+    (
+      self.corners.sym_coord(&CornerPerm, &cc.to_cube()),
+      ud_edge_perm(cc),
+      slice_perm(cc),
+    )
+  }
+}
+
+impl Default for Phase2Info {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::coords::corner_perm;
+  use cube::{parse_moves, Cube};
+
+  #[test]
+  fn sym_reduced_corners() {
+    let info = Phase2Info::new();
+    // The full table the sym-reduced one replaces.
+    let move_set = info.move_set();
+    let corners = gen_transition_table(&CornerPerm, move_set);
+    let solved = CubieCube::solved();
+    let corners_slice = gen_prune_table(
+      &gen_product_table(&corners, &info.slice),
+      corner_perm(&solved) * SlicePerm::NUM_ELEMS + slice_perm(&solved),
+    );
+    assert!(info.corners_slice.len() * 7 < corners_slice.len());
+    let entry = |(class, sym): SymCoord, slice: u32| {
+      let slice = info.slice_conj.conjugate(slice, sym as usize);
+      info.corners_slice[(class * SlicePerm::NUM_ELEMS + slice) as usize]
+    };
+    for corners in (0..CornerPerm::NUM_ELEMS).step_by(97) {
+      let coord = info
+        .corners
+        .sym_coord(&CornerPerm, &CornerPerm.from_index(corners));
+      for slice in 0..SlicePerm::NUM_ELEMS {
+        let index = corners * SlicePerm::NUM_ELEMS + slice;
+        assert_eq!(corners_slice[index as usize], entry(coord, slice));
+      }
+    }
+
+    let mut c = Cube::solved();
+    let mut state = info.state(&CubieCube::from_cube(&c).unwrap());
+    for m in parse_moves("U R2 D' F2 L2 U2 B2 D R2 U' F2").unwrap() {
+      let i = move_set.position(&m).unwrap();
+      for _ in 0..m.amount() / move_set.moves()[i].amount() {
+        state = info.transition(&state, i);
+      }
+      c.do_move(m);
+      let cc = CubieCube::from_cube(&c).unwrap();
+      // The corners may have more than one symmetry, so only compare the
+      // class.
+      assert_eq!(info.state(&cc).0 .0, state.0 .0);
+      let index = corner_perm(&cc) * SlicePerm::NUM_ELEMS + slice_perm(&cc);
+      assert_eq!(corners_slice[index as usize], entry(state.0, state.2));
+    }
+  }
+}
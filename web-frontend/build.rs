@@ -0,0 +1,17 @@
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+fn main() {
+  println!("cargo:rerun-if-changed=build.rs");
+  if env::var_os("CARGO_FEATURE_PREBUILT_TABLES").is_none() {
+    return;
+  }
+
+  let out_dir = env::var("OUT_DIR").unwrap();
+  let file = File::create(Path::new(&out_dir).join("fb.tables")).unwrap();
+  roux::first_block::FBInfo::new()
+    .save(&mut BufWriter::new(file))
+    .unwrap();
+}
@@ -46,6 +46,9 @@ self.onmessage = function(msg) {
   console.time("solve_fb");
   wasm.exports.solve_fb(scramble, orientations);
   console.timeEnd("solve_fb");
+  const solutions = JSON.parse(get_stack_str(wasm));
+  wasm.exports.scramble_svg(scramble, 0);
+  const svg = get_stack_str(wasm);
   wasm.exports.dealloc_rust_string(scramble);
-  self.postMessage(JSON.parse(get_stack_str(wasm)));
+  self.postMessage({solutions: solutions, svg: svg});
 }
@@ -1,8 +1,7 @@
 use std::ffi::CString;
 use std::os::raw::c_char;
 
-/// Imports from JS.
-#[no_mangle]
+// Imports from JS.
 extern "C" {
   fn stack_push(val: usize) -> ();
 }
@@ -44,6 +43,7 @@ impl JSString {
   }
 
   /// Consume the JSString and return a `Box<String>`.
+  #[allow(clippy::box_collection)]
   pub fn into_boxed_string(self) -> Box<String> {
     unsafe { Box::from_raw(self.0) }
   }
@@ -1,10 +1,11 @@
 use cube::parse_moves;
+use cube::svg::{self, SvgOptions};
 use cube::Cube;
 use lazy_static::lazy_static;
 use miniserde::{json, MiniSerialize};
 use roux::first_block::*;
-use solver::iddfs::iddfs;
 use solver::index::Index;
+use solver::solve::{SolveOptions, Solver};
 
 mod interop;
 
@@ -18,7 +19,18 @@ struct FBSolution {
 }
 
 lazy_static! {
-  static ref FB_INFO: FBInfo = { FBInfo::new() };
+  static ref FB_INFO: FBInfo = fb_info();
+}
+
+#[cfg(feature = "prebuilt-tables")]
+fn fb_info() -> FBInfo {
+  let tables = include_bytes!(concat!(env!("OUT_DIR"), "/fb.tables"));
+  FBInfo::load(&mut &tables[..], &"U D F B R L M".parse().unwrap()).unwrap()
+}
+
+#[cfg(not(feature = "prebuilt-tables"))]
+fn fb_info() -> FBInfo {
+  FBInfo::new()
 }
 
 fn skip_orientation(o: usize, orientations: u32) -> bool {
@@ -32,7 +44,7 @@ pub fn solve_fb(s: JSString, orientations: u32) {
   let mut solutions = Vec::with_capacity(24);
 
   let mut c = Cube::solved();
-  let scramble = parse_moves(&s.as_string()).unwrap();
+  let scramble = parse_moves(s.as_string()).unwrap();
   c.do_moves(&scramble);
 
   for &o in cube::sticker_cube::EdgePos::natural_order()
@@ -40,42 +52,38 @@ pub fn solve_fb(s: JSString, orientations: u32) {
     .filter(|&&o| skip_orientation(o as usize, orientations))
   {
     let mut c = c;
-    c.do_moves(&roux::DL_ORIENTATIONS[o as usize]);
+    c.do_moves(roux::DL_ORIENTATIONS[o as usize]);
     let (fbe, fbc) = info.get_indexes(&c);
     for x in 0..4 {
-      let mut solution = Vec::with_capacity(10);
-      for i in 0..10 {
-        let solved = iddfs(
-          (fbe.from_cube(&c), fbc.from_cube(&c)),
-          info,
-          i,
-          &mut solution,
-        );
-        if solved {
-          // A move is 1 or 2 characters, and a space between moves.
-          // So allocate 3 * i, for the maximum solution length.
-          let solution_len_max =
-            3 * (i + roux::DL_ORIENTATIONS[o as usize].len());
-          let mut ret = String::with_capacity(solution_len_max);
-          for m in roux::DL_ORIENTATIONS[o as usize] {
-            ret.push_str(&format!("{} ", m));
-          }
-          if x > 0 {
-            ret.push_str(&format!(
-              "{} ",
-              cube::Move::Rotation(cube::Rotation::X, x)
-            ));
-          }
-          for m in &solution {
-            ret.push_str(&format!("{} ", m));
-          }
-          solutions.push(FBSolution {
-            moves: ret,
-            len: solution.len(),
-            dl: String::from(format!("{:?}", o)),
-          });
-          break;
+      let options = SolveOptions {
+        max_depth: 9,
+        ..Default::default()
+      };
+      let result = Solver::new(info, options)
+        .solve((fbe.from_cube(&c), fbc.from_cube(&c)));
+      if let Some(solution) = result.solution {
+        // A move is 1 or 2 characters, and a space between moves.
+        // So allocate 3 * len, for the maximum solution length.
+        let solution_len_max =
+          3 * (solution.len() + roux::DL_ORIENTATIONS[o as usize].len());
+        let mut ret = String::with_capacity(solution_len_max);
+        for m in roux::DL_ORIENTATIONS[o as usize] {
+          ret.push_str(&format!("{} ", m));
+        }
+        if x > 0 {
+          ret.push_str(&format!(
+            "{} ",
+            cube::Move::Rotation(cube::Rotation::X, x)
+          ));
+        }
+        for m in &solution {
+          ret.push_str(&format!("{} ", m));
         }
+        solutions.push(FBSolution {
+          moves: ret,
+          len: solution.len(),
+          dl: format!("{:?}", o),
+        });
       }
       c.do_move(cube::Move::Rotation(cube::Rotation::X, 1));
     }
@@ -84,3 +92,19 @@ pub fn solve_fb(s: JSString, orientations: u32) {
   solutions.sort_by_key(|a| a.len);
   stack_push_str(&json::to_string(&solutions));
 }
+
+/// Draw the cube after the scramble `s` as an SVG image. `view` is 0 for an
+/// isometric view, 1 for a net and 2 for a last layer diagram.
+#[no_mangle]
+pub fn scramble_svg(s: JSString, view: u32) {
+  let mut c = Cube::solved();
+  c.do_moves(&parse_moves(s.as_string()).unwrap());
+
+  let options = SvgOptions::default();
+  let svg = match view {
+    0 => svg::isometric(&c, &options),
+    1 => svg::net(&c, &options),
+    _ => svg::last_layer(&c, &options),
+  };
+  stack_push_str(&svg);
+}